@@ -1,17 +1,33 @@
 use std::sync::{Arc, Mutex};
 
 use crate::{
-    exports::example::game::game_api::{KeyboardInfo, MouseInfo},
-    infrastructure::Screen,
-    ui::{ScreenExt, TextSize},
+    example::game::types::{
+        ActionDeclaration, ActionKind, BuildInfo, GameColor, ImageParams, InputEvent, Key,
+        MouseButton, Rect, ScalingMode, Transform, VirtualResolution,
+    },
+    exports::example::game::game_api::FrameInput,
+    infrastructure::{
+        input,
+        log::{self, LogLevel},
+        storage, window, Screen,
+    },
+    input_tracker::{Control, InputTracker},
+    ui::{ScreenExt, TextField, TextSize},
     AQUA, BLUE, RED,
 };
 
 use serde::{Deserialize, Serialize};
 
+const NAME_SLOT: &str = "name";
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct GameState {
     count: u32,
+    name: TextField,
+    spin: f32,
+    tracker: InputTracker,
+    fullscreen: bool,
+    reloads: u32,
 }
 
 pub struct Game {
@@ -21,14 +37,53 @@ pub struct Game {
 impl Default for Game {
     fn default() -> Self {
         Self {
-            state: Arc::new(Mutex::new(GameState { count: 0 })),
+            state: Arc::new(Mutex::new(GameState {
+                count: 0,
+                name: TextField::new("Ferris"),
+                spin: 0.0,
+                tracker: InputTracker::new(),
+                fullscreen: false,
+                reloads: 0,
+            })),
         }
     }
 }
 
 impl Game {
     pub fn new() -> Game {
-        Game::default()
+        input::set_event_stream(true);
+        input::declare_actions(&[
+            ActionDeclaration {
+                name: "increment".to_string(),
+                kind: ActionKind::Button,
+                default_bindings: vec!["key:space".to_string(), "pad:south".to_string()],
+            },
+            ActionDeclaration {
+                name: "spin".to_string(),
+                kind: ActionKind::Axis,
+                default_bindings: vec![
+                    "key:right".to_string(),
+                    "-key:left".to_string(),
+                    "pad:left-stick-x".to_string(),
+                ],
+            },
+        ]);
+        window::set_clear_color(GameColor {
+            r: 0.1,
+            g: 0.1,
+            b: 0.15,
+            a: 1.0,
+        });
+        let game = Game::default();
+        // The name is kept between runs, the rest of the state only survives reloads
+        match storage::get(NAME_SLOT) {
+            Ok(Some(name)) => {
+                game.state.lock().unwrap().name.text = String::from_utf8_lossy(&name).to_string()
+            }
+            Ok(None) => {}
+            Err(e) => log::write(LogLevel::Warn, "game::storage", &e),
+        }
+        game
     }
 
     pub fn save(&self) -> Vec<u8> {
@@ -39,10 +94,92 @@ impl Game {
         *self.state.lock().unwrap() = bincode::deserialize(&data).expect("Unable to restore state");
     }
 
-    pub fn run_frame(&self, mouse: MouseInfo, key: KeyboardInfo, screen: &Screen) {
-        if mouse.left.pressed {
+    pub fn debug_state(&self) -> String {
+        format!("{:#?}", *self.state.lock().unwrap())
+    }
+
+    pub fn on_before_reload(&self) {
+        log::write(
+            LogLevel::Info,
+            "game::reload",
+            &format!("Reloading with count {}", self.state.lock().unwrap().count),
+        );
+    }
+
+    // Called on the new build, after the state saved by the old one has been restored
+    pub fn on_after_reload(&self, previous: BuildInfo) {
+        let mut state = self.state.lock().unwrap();
+        state.reloads += 1;
+        log::write(
+            LogLevel::Info,
+            "game::reload",
+            &format!(
+                "Reloaded over build {} ({} bytes), {} reloads so far",
+                previous.generation, previous.size_bytes, state.reloads
+            ),
+        );
+    }
+
+    pub fn on_exit(&self) {
+        log::write(
+            LogLevel::Info,
+            "game::exit",
+            &format!("Exiting with count {}", self.state.lock().unwrap().count),
+        );
+    }
+
+    pub fn run_frame(&self, input: FrameInput, screen: &Screen) {
+        self.state.lock().unwrap().tracker.update(&input);
+
+        let FrameInput {
+            mouse,
+            key,
+            gamepads,
+            events,
+            actions,
+            ..
+        } = input;
+
+        screen.set_virtual_resolution(Some(VirtualResolution {
+            size: (800.0, 600.0).into(),
+            scaling: ScalingMode::Fit,
+        }));
+
+        // Actions share keys with the name field, so they are ignored while typing
+        let typing = self.state.lock().unwrap().name.focused;
+        let action = |name: &str| {
+            actions
+                .iter()
+                .find(|action| action.name == name && !typing)
+                .cloned()
+        };
+        let increment = action("increment").is_some_and(|action| action.pressed);
+        // The event stream keeps every click, even several landing in one slow frame
+        let clicks = match &events {
+            Some(events) => events
+                .iter()
+                .filter(|event| matches!(event, InputEvent::MouseDown(MouseButton::Left)))
+                .count(),
+            None => mouse.left.pressed as usize,
+        };
+        {
             let mut state = self.state.lock().unwrap();
-            state.count += 1;
+            state.count += clicks as u32 + increment as u32;
+            state.spin += action("spin").map_or(0.0, |action| action.value) * 0.1;
+
+            if state.tracker.chord(&[Control::Ctrl, Key::R.into()]) {
+                state.count = 0;
+            }
+            if state.tracker.double_click(MouseButton::Right) {
+                state.spin = 0.0;
+            }
+            if state.tracker.pressed(Key::F11) {
+                state.fullscreen = !state.fullscreen;
+                window::set_fullscreen(state.fullscreen);
+            }
+            if state.tracker.chord(&[Control::Ctrl, Key::Q.into()]) {
+                window::quit();
+            }
         }
 
         screen.text(
@@ -51,25 +188,82 @@ impl Game {
             TextSize::Title,
             AQUA,
         );
+        let title_size =
+            screen.measure_text("Hot Reloading with Rust!", TextSize::Title.into(), None);
+        screen.draw_line(
+            (40.0, 90.0).into(),
+            (40.0 + title_size.width, 90.0).into(),
+            2.0,
+            AQUA,
+        );
         screen.draw_image(
             "resources/rustacean-flat-happy.png",
             (500.0, 25.0).into(),
             Some((150.0, 90.0).into()),
         );
-
-        screen.standard_text(
-            &format!("Count: {}", self.state.lock().unwrap().count),
-            (40.0, 120.0),
+        screen.draw_image_ex(
+            "resources/rustacean-flat-happy.png",
+            (500.0, 250.0).into(),
+            ImageParams {
+                size: Some((75.0, 45.0).into()),
+                rotation: 0.3,
+                flip_x: true,
+                tint: AQUA,
+                ..Default::default()
+            },
         );
 
-        screen.standard_text(&format!("Key Down: ({:?})", key.down), (40.0, 160.0).into());
+        {
+            let state = self.state.lock().unwrap();
+            screen.standard_text(
+                &format!("Count: {} Reloads: {}", state.count, state.reloads),
+                (40.0, 120.0),
+            );
+        }
+
+        screen.standard_text(&format!("Key Down: ({:?})", key.down), (40.0, 160.0));
 
         screen.standard_text(
-            &format!("Mouse: ({}, {})", mouse.position.x, mouse.position.y),
-            (40.0, 185.0).into(),
+            &format!(
+                "Mouse: ({}, {}) Delta: ({}, {}) Wheel: {}",
+                mouse.position.x, mouse.position.y, mouse.delta.x, mouse.delta.y, mouse.wheel.y
+            ),
+            (40.0, 185.0),
         );
 
+        for (index, pad) in gamepads.iter().enumerate() {
+            screen.standard_text(
+                &format!(
+                    "{}: ({:.2}, {:.2}) {:?}",
+                    pad.name, pad.left_stick.x, pad.left_stick.y, pad.down
+                ),
+                (40.0, 210.0 + index as f32 * 25.0),
+            );
+        }
+
+        {
+            let name_bounds: Rect = (40.0, 500.0, 300.0, 30.0).into();
+            let mut state = self.state.lock().unwrap();
+            if state.name.update(name_bounds, &mouse, &key) {
+                state.name.focused = false;
+                if let Err(e) = storage::put(NAME_SLOT, state.name.text.as_bytes()) {
+                    log::write(LogLevel::Warn, "game::storage", &e);
+                }
+            }
+            state.name.draw(screen, name_bounds, AQUA);
+            screen.standard_text(&format!("Hello, {}!", state.name.text), (40.0, 560.0));
+        }
+
         screen.draw_line((625.0, 125.0).into(), (675.0, 200.0).into(), 4.0, RED);
         screen.draw_line((700.0, 125.0).into(), (700.0, 200.0).into(), 4.0, BLUE);
+
+        screen.push_transform(Transform {
+            translation: (700.0, 400.0).into(),
+            rotation: self.state.lock().unwrap().spin,
+            ..Default::default()
+        });
+        screen.draw_line((-25.0, 0.0).into(), (25.0, 0.0).into(), 4.0, RED);
+        screen.draw_line((0.0, -25.0).into(), (0.0, 25.0).into(), 4.0, BLUE);
+        screen.pop_transform();
     }
 }
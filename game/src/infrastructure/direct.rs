@@ -1,12 +1,271 @@
+use std::{any::Any, sync::OnceLock};
+
 use async_trait::async_trait;
 
-use crate::example::game::types::{GameColor, Position, Size};
+use crate::example::game::host_audio::PlayParams;
+use crate::example::game::host_log::LogLevel;
+use crate::example::game::types::{
+    ActionDeclaration, FilterMode, GameCamera, GameColor, ImageParams, Position, Rect, Size,
+    Transform, VirtualResolution,
+};
 
 #[async_trait]
 pub trait GameScreenInterface: Send + Sync {
-    fn draw_text(&self, text: &str, position: Position, size: f32, color: GameColor);
+    fn draw_text(
+        &self,
+        text: &str,
+        position: Position,
+        size: f32,
+        color: GameColor,
+        font: Option<&Font>,
+    );
+    fn measure_text(&self, text: &str, size: f32, font: Option<&Font>) -> Size;
     fn draw_line(&self, first: Position, second: Position, thickness: f32, color: GameColor);
     fn draw_image(&self, filename: &str, position: Position, size: Option<Size>);
+    fn draw_image_ex(&self, filename: &str, position: Position, params: ImageParams);
+    fn draw_render_target(&self, target: &RenderTarget, position: Position, params: ImageParams);
+    fn draw_texture(&self, texture: &Texture, position: Position, params: ImageParams);
+    fn clear(&self, color: GameColor);
+    fn set_virtual_resolution(&self, resolution: Option<VirtualResolution>);
+    fn set_cursor_visible(&self, visible: bool);
+    fn set_cursor_grab(&self, grab: bool);
+    fn present_framebuffer(&self, width: u32, height: u32, pixels: &[u8], filter: FilterMode);
+    fn push_transform(&self, transform: Transform);
+    fn pop_transform(&self);
+    fn set_camera(&self, camera: Option<GameCamera>);
+    fn world_to_screen(&self, position: Position) -> Position;
+    fn screen_to_world(&self, position: Position) -> Position;
+    fn push_clip(&self, rect: Rect);
+    fn pop_clip(&self);
+    fn push_render_target(&self, target: &RenderTarget);
+    fn pop_render_target(&self);
 }
 
 pub type GameScreen = dyn GameScreenInterface;
+
+// Fonts are resolved by path on the launcher side, so outside of wasm there is nothing to hold
+pub struct Font {
+    path: String,
+}
+
+impl Font {
+    pub fn new(path: &str) -> Font {
+        Font {
+            path: path.to_string(),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+// Host objects are opaque to the game, only the launcher knows their concrete type
+pub type HostHandle = Box<dyn Any + Send + Sync>;
+
+// Direct mode equivalent of the host-api imports which are not tied to the game screen
+pub trait HostInterface: Send + Sync {
+    fn create_render_target(&self, size: Size) -> HostHandle;
+    fn create_texture(&self, width: u32, height: u32, pixels: &[u8]) -> HostHandle;
+    fn update_texture(&self, texture: &HostHandle, pixels: &[u8]);
+    fn play_sound(&self, path: &str, params: PlayParams);
+    fn stop_sound(&self, path: &str);
+    fn set_sound_volume(&self, path: &str, volume: f32);
+    fn play_music(&self, path: &str, volume: f32);
+    fn stop_music(&self);
+    fn set_music_volume(&self, volume: f32);
+    fn set_event_stream(&self, enabled: bool);
+    fn declare_actions(&self, actions: &[ActionDeclaration]);
+    fn set_window_title(&self, title: &str);
+    fn set_fullscreen(&self, fullscreen: bool);
+    fn set_clear_color(&self, color: GameColor);
+    fn resize_window(&self, size: Size);
+    fn quit(&self);
+    fn log(&self, level: LogLevel, target: &str, message: &str);
+    fn read_asset(&self, path: &str) -> Result<Vec<u8>, String>;
+    fn read_asset_dir(&self, path: &str) -> Result<Vec<String>, String>;
+    fn storage_get(&self, slot: &str) -> Result<Option<Vec<u8>>, String>;
+    fn storage_put(&self, slot: &str, data: &[u8]) -> Result<(), String>;
+    fn storage_delete(&self, slot: &str) -> Result<(), String>;
+    fn storage_slots(&self) -> Result<Vec<String>, String>;
+}
+
+static HOST: OnceLock<Box<dyn HostInterface>> = OnceLock::new();
+
+pub fn set_host(host: Box<dyn HostInterface>) {
+    if HOST.set(host).is_err() {
+        panic!("Host can only be set once");
+    }
+}
+
+fn host() -> &'static dyn HostInterface {
+    HOST.get().expect("Host must be set before use").as_ref()
+}
+
+pub struct RenderTarget {
+    handle: HostHandle,
+}
+
+impl RenderTarget {
+    pub fn new(size: Size) -> RenderTarget {
+        RenderTarget {
+            handle: host().create_render_target(size),
+        }
+    }
+
+    pub fn handle(&self) -> &HostHandle {
+        &self.handle
+    }
+}
+
+pub struct Texture {
+    handle: HostHandle,
+}
+
+impl Texture {
+    pub fn new(width: u32, height: u32, pixels: &[u8]) -> Texture {
+        Texture {
+            handle: host().create_texture(width, height, pixels),
+        }
+    }
+
+    pub fn update(&self, pixels: &[u8]) {
+        host().update_texture(&self.handle, pixels);
+    }
+
+    pub fn handle(&self) -> &HostHandle {
+        &self.handle
+    }
+}
+
+// Direct mode equivalent of the host-audio imports, with the same names as the generated bindings
+pub mod audio {
+    pub use crate::example::game::host_audio::PlayParams;
+
+    use super::host;
+
+    // Sounds are resolved by path on the launcher side, like fonts
+    pub struct Sound {
+        path: String,
+    }
+
+    impl Sound {
+        pub fn new(path: &str) -> Sound {
+            Sound {
+                path: path.to_string(),
+            }
+        }
+    }
+
+    pub fn play(sound: &Sound, params: PlayParams) {
+        host().play_sound(&sound.path, params);
+    }
+
+    pub fn stop(sound: &Sound) {
+        host().stop_sound(&sound.path);
+    }
+
+    pub fn set_volume(sound: &Sound, volume: f32) {
+        host().set_sound_volume(&sound.path, volume);
+    }
+
+    pub fn play_music(path: &str, volume: f32) {
+        host().play_music(path, volume);
+    }
+
+    pub fn stop_music() {
+        host().stop_music();
+    }
+
+    pub fn set_music_volume(volume: f32) {
+        host().set_music_volume(volume);
+    }
+}
+
+// Direct mode equivalent of the host-input imports
+pub mod input {
+    pub use crate::example::game::types::ActionDeclaration;
+
+    use super::host;
+
+    pub fn set_event_stream(enabled: bool) {
+        host().set_event_stream(enabled);
+    }
+
+    pub fn declare_actions(actions: &[ActionDeclaration]) {
+        host().declare_actions(actions);
+    }
+}
+
+// Direct mode equivalent of the host-window imports
+pub mod window {
+    use super::host;
+    use crate::example::game::types::{GameColor, Size};
+
+    pub fn set_title(title: &str) {
+        host().set_window_title(title);
+    }
+
+    pub fn set_fullscreen(fullscreen: bool) {
+        host().set_fullscreen(fullscreen);
+    }
+
+    pub fn set_clear_color(color: GameColor) {
+        host().set_clear_color(color);
+    }
+
+    pub fn resize(size: Size) {
+        host().resize_window(size);
+    }
+
+    pub fn quit() {
+        host().quit();
+    }
+}
+
+// Direct mode equivalent of the host-log imports
+// The game shares the launcher's process here, so its stdout and stderr are not captured
+pub mod log {
+    pub use crate::example::game::host_log::LogLevel;
+
+    use super::host;
+
+    pub fn write(level: LogLevel, target: &str, message: &str) {
+        host().log(level, target, message);
+    }
+}
+
+// Direct mode equivalent of the host-assets imports, going through the launcher so paths resolve
+// against the same assets directory as in hotreload mode
+pub mod assets {
+    use super::host;
+
+    pub fn read(path: &str) -> Result<Vec<u8>, String> {
+        host().read_asset(path)
+    }
+
+    pub fn read_dir(path: &str) -> Result<Vec<String>, String> {
+        host().read_asset_dir(path)
+    }
+}
+
+// Direct mode equivalent of the host-storage imports
+pub mod storage {
+    use super::host;
+
+    pub fn get(slot: &str) -> Result<Option<Vec<u8>>, String> {
+        host().storage_get(slot)
+    }
+
+    pub fn put(slot: &str, data: &[u8]) -> Result<(), String> {
+        host().storage_put(slot, data)
+    }
+
+    pub fn delete(slot: &str) -> Result<(), String> {
+        host().storage_delete(slot)
+    }
+
+    pub fn slots() -> Result<Vec<String>, String> {
+        host().storage_slots()
+    }
+}
@@ -1,7 +1,19 @@
+use std::{panic::PanicHookInfo, sync::Once};
+
+use crate::example::game::host_log::{report_panic, PanicLocation};
+use crate::example::game::types::BuildInfo;
+use crate::exports::example::game::game_api::{FrameInput, GameInstanceBorrow};
 use crate::exports::example::game::game_api::{Guest, GuestGameInstance};
-use crate::exports::example::game::game_api::{KeyboardInfo, MouseInfo};
+use crate::exports::example::game::game_debug::Guest as DebugGuest;
+use crate::exports::example::game::game_lifecycle::Guest as LifecycleGuest;
 
-pub use crate::example::game::host_api::GameScreen;
+pub use crate::example::game::host_api::{Font, GameScreen, RenderTarget, Texture};
+pub use crate::example::game::host_assets as assets;
+pub use crate::example::game::host_audio as audio;
+pub use crate::example::game::host_input as input;
+pub use crate::example::game::host_log as log;
+pub use crate::example::game::host_storage as storage;
+pub use crate::example::game::host_window as window;
 
 use crate::Game;
 
@@ -11,8 +23,28 @@ impl Guest for GameGuest {
     type GameInstance = Game;
 }
 
+// Panics trap without a message in wasm, so the hook sends it to the host before that happens
+fn report(info: &PanicHookInfo) {
+    let payload = info.payload();
+    let message = match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "Box<dyn Any>".to_string(),
+        },
+    };
+    let location = info.location().map(|location| PanicLocation {
+        file: location.file().to_string(),
+        line: location.line(),
+        column: location.column(),
+    });
+    report_panic(&message, location.as_ref());
+}
+
 impl GuestGameInstance for Game {
     fn new() -> Game {
+        static HOOK: Once = Once::new();
+        HOOK.call_once(|| std::panic::set_hook(Box::new(report)));
         Game::new()
     }
 
@@ -24,7 +56,27 @@ impl GuestGameInstance for Game {
         Game::restore(self, data)
     }
 
-    fn run_frame(&self, mouse: MouseInfo, key: KeyboardInfo, screen: &GameScreen) {
-        Game::run_frame(self, mouse, key, screen);
+    fn run_frame(&self, input: FrameInput, screen: &GameScreen) {
+        Game::run_frame(self, input, screen);
+    }
+}
+
+impl LifecycleGuest for GameGuest {
+    fn on_before_reload(instance: GameInstanceBorrow<'_>) {
+        instance.get::<Game>().on_before_reload();
+    }
+
+    fn on_after_reload(instance: GameInstanceBorrow<'_>, previous: BuildInfo) {
+        instance.get::<Game>().on_after_reload(previous);
+    }
+
+    fn on_exit(instance: GameInstanceBorrow<'_>) {
+        instance.get::<Game>().on_exit();
+    }
+}
+
+impl DebugGuest for GameGuest {
+    fn debug_state(instance: GameInstanceBorrow<'_>) -> String {
+        instance.get::<Game>().debug_state()
     }
 }
@@ -1,7 +1,9 @@
 #[cfg(feature = "hotreload")]
 mod hotreload;
 #[cfg(feature = "hotreload")]
-pub use hotreload::{GameGuest, GameScreen};
+pub use hotreload::{
+    assets, audio, input, log, storage, window, Font, GameGuest, GameScreen, RenderTarget, Texture,
+};
 #[cfg(feature = "hotreload")]
 pub type Screen = GameScreen;
 
@@ -12,4 +14,7 @@ use direct::GameScreen;
 #[cfg(not(feature = "hotreload"))]
 pub type Screen = GameScreen;
 #[cfg(not(feature = "hotreload"))]
-pub use direct::GameScreenInterface;
+pub use direct::{
+    assets, audio, input, log, set_host, storage, window, Font, GameScreenInterface, HostHandle,
+    HostInterface, RenderTarget, Texture,
+};
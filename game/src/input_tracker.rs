@@ -0,0 +1,741 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    example::game::types::{Key, MouseButton},
+    exports::example::game::game_api::FrameInput,
+};
+
+// Presses older than this are forgotten, so it bounds how slow a sequence can be typed
+const HISTORY_SECONDS: f64 = 2.0;
+pub const DOUBLE_CLICK_SECONDS: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Key(Key),
+    Mouse(MouseButton),
+    // Modifiers match either the left or the right key
+    Shift,
+    Ctrl,
+    Alt,
+    Logo,
+}
+
+impl Control {
+    // Generated WIT types can't be serialized, so controls are stored by a number instead
+    fn id(self) -> u16 {
+        match self {
+            Control::Key(key) => key_id(key),
+            Control::Mouse(MouseButton::Left) => 0x100,
+            Control::Mouse(MouseButton::Right) => 0x101,
+            Control::Mouse(MouseButton::Middle) => 0x102,
+            Control::Shift => 0x200,
+            Control::Ctrl => 0x201,
+            Control::Alt => 0x202,
+            Control::Logo => 0x203,
+        }
+    }
+}
+
+// Fixed numbers rather than the enum order, so adding a key to the WIT enum can't remap a saved
+// tracker, new keys fail to compile here until they get a number of their own
+fn key_id(key: Key) -> u16 {
+    match key {
+        Key::Space => 0,
+        Key::Apostrophe => 1,
+        Key::Comma => 2,
+        Key::Minus => 3,
+        Key::Period => 4,
+        Key::Slash => 5,
+        Key::KeyZero => 6,
+        Key::KeyOne => 7,
+        Key::KeyTwo => 8,
+        Key::KeyThree => 9,
+        Key::KeyFour => 10,
+        Key::KeyFive => 11,
+        Key::KeySix => 12,
+        Key::KeySeven => 13,
+        Key::KeyEight => 14,
+        Key::KeyNine => 15,
+        Key::Semicolon => 16,
+        Key::Equal => 17,
+        Key::A => 18,
+        Key::B => 19,
+        Key::C => 20,
+        Key::D => 21,
+        Key::E => 22,
+        Key::F => 23,
+        Key::G => 24,
+        Key::H => 25,
+        Key::I => 26,
+        Key::J => 27,
+        Key::K => 28,
+        Key::L => 29,
+        Key::M => 30,
+        Key::N => 31,
+        Key::O => 32,
+        Key::P => 33,
+        Key::Q => 34,
+        Key::R => 35,
+        Key::S => 36,
+        Key::T => 37,
+        Key::U => 38,
+        Key::V => 39,
+        Key::W => 40,
+        Key::X => 41,
+        Key::Y => 42,
+        Key::Z => 43,
+        Key::LeftBracket => 44,
+        Key::Backslash => 45,
+        Key::RightBracket => 46,
+        Key::GraveAccent => 47,
+        Key::WorldOne => 48,
+        Key::WorldTwo => 49,
+        Key::Escape => 50,
+        Key::Enter => 51,
+        Key::Tab => 52,
+        Key::Backspace => 53,
+        Key::Insert => 54,
+        Key::Delete => 55,
+        Key::Right => 56,
+        Key::Left => 57,
+        Key::Down => 58,
+        Key::Up => 59,
+        Key::PageUp => 60,
+        Key::PageDown => 61,
+        Key::Home => 62,
+        Key::End => 63,
+        Key::CapsLock => 64,
+        Key::ScrollLock => 65,
+        Key::NumLock => 66,
+        Key::PrintScreen => 67,
+        Key::Pause => 68,
+        Key::F1 => 69,
+        Key::F2 => 70,
+        Key::F3 => 71,
+        Key::F4 => 72,
+        Key::F5 => 73,
+        Key::F6 => 74,
+        Key::F7 => 75,
+        Key::F8 => 76,
+        Key::F9 => 77,
+        Key::F10 => 78,
+        Key::F11 => 79,
+        Key::F12 => 80,
+        Key::F13 => 81,
+        Key::F14 => 82,
+        Key::F15 => 83,
+        Key::F16 => 84,
+        Key::F17 => 85,
+        Key::F18 => 86,
+        Key::F19 => 87,
+        Key::F20 => 88,
+        Key::F21 => 89,
+        Key::F22 => 90,
+        Key::F23 => 91,
+        Key::F24 => 92,
+        Key::F25 => 93,
+        Key::Kp0 => 94,
+        Key::Kp1 => 95,
+        Key::Kp2 => 96,
+        Key::Kp3 => 97,
+        Key::Kp4 => 98,
+        Key::Kp5 => 99,
+        Key::Kp6 => 100,
+        Key::Kp7 => 101,
+        Key::Kp8 => 102,
+        Key::Kp9 => 103,
+        Key::KpDecimal => 104,
+        Key::KpDivide => 105,
+        Key::KpMultiply => 106,
+        Key::KpSubtract => 107,
+        Key::KpAdd => 108,
+        Key::KpEnter => 109,
+        Key::KpEqual => 110,
+        Key::LeftShift => 111,
+        Key::LeftControl => 112,
+        Key::LeftAlt => 113,
+        Key::LeftSuper => 114,
+        Key::RightShift => 115,
+        Key::RightControl => 116,
+        Key::RightAlt => 117,
+        Key::RightSuper => 118,
+        Key::Menu => 119,
+        Key::Unknown => 120,
+    }
+}
+
+impl From<Key> for Control {
+    fn from(value: Key) -> Self {
+        Control::Key(value)
+    }
+}
+
+impl From<MouseButton> for Control {
+    fn from(value: MouseButton) -> Self {
+        Control::Mouse(value)
+    }
+}
+
+// Tracks keys and mouse buttons over time, kept in the game state so it survives hot reloads
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InputTracker {
+    time: f64,
+    previous_time: f64,
+    // When each held control went down
+    down: HashMap<u16, f64>,
+    pressed: Vec<u16>,
+    released: Vec<u16>,
+    // Recent presses, oldest first
+    history: Vec<(u16, f64)>,
+}
+
+impl InputTracker {
+    pub fn new() -> InputTracker {
+        InputTracker::default()
+    }
+
+    pub fn update(&mut self, input: &FrameInput) {
+        self.previous_time = self.time;
+        self.time = input.time;
+
+        let key = &input.key;
+        let mouse = &input.mouse;
+        let mut pressed: Vec<Control> = key.pressed.iter().map(|key| (*key).into()).collect();
+        let mut released: Vec<Control> = key.released.iter().map(|key| (*key).into()).collect();
+        let mut down: Vec<Control> = key.down.iter().map(|key| (*key).into()).collect();
+
+        for (button, click) in [
+            (MouseButton::Left, &mouse.left),
+            (MouseButton::Right, &mouse.right),
+            (MouseButton::Middle, &mouse.middle),
+        ] {
+            if click.pressed {
+                pressed.push(button.into());
+            }
+            if click.released {
+                released.push(button.into());
+            }
+            if click.down {
+                down.push(button.into());
+            }
+        }
+
+        // Modifiers only report their current state, so their edges come from the previous frame
+        for (modifier, held) in [
+            (Control::Shift, key.modifiers.shift),
+            (Control::Ctrl, key.modifiers.control),
+            (Control::Alt, key.modifiers.alt),
+            (Control::Logo, key.modifiers.logo),
+        ] {
+            let was_held = self.down.contains_key(&modifier.id());
+            if held {
+                down.push(modifier);
+                if !was_held {
+                    pressed.push(modifier);
+                }
+            } else if was_held {
+                released.push(modifier);
+            }
+        }
+
+        self.pressed = pressed.iter().map(|control| control.id()).collect();
+        self.released = released.iter().map(|control| control.id()).collect();
+
+        let down: Vec<u16> = down.iter().map(|control| control.id()).collect();
+        self.down.retain(|id, _| down.contains(id));
+        for id in down.iter().chain(&self.pressed) {
+            self.down.entry(*id).or_insert(self.time);
+        }
+        // Pressed and released within one frame still counts as a press, but isn't held
+        for id in &self.released {
+            if !down.contains(id) {
+                self.down.remove(id);
+            }
+        }
+
+        let time = self.time;
+        self.history
+            .extend(self.pressed.iter().map(|id| (*id, time)));
+        self.history
+            .retain(|(_, pressed_at)| time - pressed_at <= HISTORY_SECONDS);
+    }
+
+    pub fn down(&self, control: impl Into<Control>) -> bool {
+        self.down.contains_key(&control.into().id())
+    }
+
+    pub fn pressed(&self, control: impl Into<Control>) -> bool {
+        self.pressed.contains(&control.into().id())
+    }
+
+    pub fn released(&self, control: impl Into<Control>) -> bool {
+        self.released.contains(&control.into().id())
+    }
+
+    // Seconds the control has been held, zero when it is up
+    pub fn held_time(&self, control: impl Into<Control>) -> f32 {
+        self.down
+            .get(&control.into().id())
+            .map_or(0.0, |since| (self.time - since) as f32)
+    }
+
+    // True only on the frame the hold reaches the duration, for "hold to confirm" style actions
+    pub fn held(&self, control: impl Into<Control>, seconds: f32) -> bool {
+        self.down.get(&control.into().id()).is_some_and(|since| {
+            let reached = since + seconds as f64;
+            reached <= self.time && reached > self.previous_time
+        })
+    }
+
+    // True on the frame the last part of the chord goes down while the others are held, like ctrl+S
+    pub fn chord(&self, controls: &[Control]) -> bool {
+        controls.iter().all(|control| self.down(*control))
+            && controls.iter().any(|control| self.pressed(*control))
+    }
+
+    // True on the frame the last control is pressed, if every press was in order and within the time
+    pub fn sequence(&self, controls: &[Control], within: f32) -> bool {
+        if controls.is_empty() || controls.len() > self.history.len() {
+            return false;
+        }
+
+        let recent = &self.history[self.history.len() - controls.len()..];
+        let (_, first_at) = recent[0];
+        let (_, last_at) = recent[recent.len() - 1];
+        last_at == self.time
+            && self.time - first_at <= within as f64
+            && recent
+                .iter()
+                .zip(controls)
+                .all(|((id, _), control)| *id == control.id())
+    }
+
+    pub fn double_click(&self, control: impl Into<Control>) -> bool {
+        let control = control.into();
+        self.sequence(&[control, control], DOUBLE_CLICK_SECONDS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    use crate::example::game::types::{
+        ClickInfo, KeyboardInfo, Modifiers, MouseInfo, Offset, Position, Size, WindowInfo,
+    };
+
+    fn click(down: bool, pressed: bool, released: bool) -> ClickInfo {
+        ClickInfo {
+            pressed,
+            released,
+            down,
+        }
+    }
+
+    // One frame of input, keys list what went down, came up and is held
+    struct Frame {
+        time: f64,
+        pressed: Vec<Key>,
+        released: Vec<Key>,
+        down: Vec<Key>,
+        left: ClickInfo,
+        control: bool,
+    }
+
+    fn frame(time: f64) -> Frame {
+        Frame {
+            time,
+            pressed: vec![],
+            released: vec![],
+            down: vec![],
+            left: click(false, false, false),
+            control: false,
+        }
+    }
+
+    impl Frame {
+        fn press(mut self, key: Key) -> Frame {
+            self.pressed.push(key);
+            self.down.push(key);
+            self
+        }
+
+        fn hold(mut self, key: Key) -> Frame {
+            self.down.push(key);
+            self
+        }
+
+        // Down and up again within the frame, so it never shows as held
+        fn tap(mut self, key: Key) -> Frame {
+            self.pressed.push(key);
+            self.released.push(key);
+            self
+        }
+
+        fn release(mut self, key: Key) -> Frame {
+            self.released.push(key);
+            self
+        }
+
+        fn click(mut self) -> Frame {
+            self.left = click(false, true, true);
+            self
+        }
+
+        fn control(mut self) -> Frame {
+            self.control = true;
+            self
+        }
+
+        fn input(self) -> FrameInput {
+            let up = click(false, false, false);
+            FrameInput {
+                time: self.time,
+                delta_time: 1.0 / 60.0,
+                mouse: MouseInfo {
+                    position: Position { x: 0.0, y: 0.0 },
+                    left: self.left,
+                    right: up,
+                    middle: up,
+                    delta: Offset { x: 0.0, y: 0.0 },
+                    wheel: Offset { x: 0.0, y: 0.0 },
+                },
+                key: KeyboardInfo {
+                    pressed: self.pressed,
+                    released: self.released,
+                    down: self.down,
+                    repeated: vec![],
+                    chars: vec![],
+                    modifiers: Modifiers {
+                        shift: false,
+                        control: self.control,
+                        alt: false,
+                        logo: false,
+                    },
+                },
+                gamepads: vec![],
+                events: None,
+                actions: vec![],
+                window: WindowInfo {
+                    size: Size {
+                        width: 800.0,
+                        height: 600.0,
+                    },
+                    events: vec![],
+                },
+            }
+        }
+    }
+
+    fn run(frames: Vec<Frame>) -> InputTracker {
+        let mut tracker = InputTracker::new();
+        for frame in frames {
+            tracker.update(&frame.input());
+        }
+        tracker
+    }
+
+    #[test]
+    fn press_and_release_in_one_frame_is_a_press_but_not_held() {
+        let tracker = run(vec![frame(0.0).tap(Key::A)]);
+        assert!(tracker.pressed(Key::A));
+        assert!(tracker.released(Key::A));
+        assert!(!tracker.down(Key::A));
+        assert_eq!(tracker.held_time(Key::A), 0.0);
+    }
+
+    #[test]
+    fn held_time_counts_from_the_press() {
+        let tracker = run(vec![frame(1.0).press(Key::A), frame(1.25).hold(Key::A)]);
+        assert!(!tracker.pressed(Key::A));
+        assert!(tracker.down(Key::A));
+        assert_eq!(tracker.held_time(Key::A), 0.25);
+
+        let tracker = run(vec![
+            frame(1.0).press(Key::A),
+            frame(1.25).hold(Key::A),
+            frame(1.5).release(Key::A),
+        ]);
+        assert!(tracker.released(Key::A));
+        assert_eq!(tracker.held_time(Key::A), 0.0);
+    }
+
+    #[test]
+    fn held_fires_once_on_the_frame_the_duration_is_reached() {
+        let mut tracker = InputTracker::new();
+        let mut fired = vec![];
+        for time in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            let frame = if time == 0.0 {
+                frame(time).press(Key::A)
+            } else {
+                frame(time).hold(Key::A)
+            };
+            tracker.update(&frame.input());
+            fired.push(tracker.held(Key::A, 0.5));
+        }
+        // Reaching the duration exactly counts
+        assert_eq!(fired, [false, false, true, false, false]);
+    }
+
+    #[test]
+    fn held_resets_after_a_release() {
+        let mut tracker = run(vec![
+            frame(0.0).press(Key::A),
+            frame(0.25).release(Key::A),
+            frame(0.5).press(Key::A),
+        ]);
+        assert!(!tracker.held(Key::A, 0.5));
+        tracker.update(&frame(1.0).hold(Key::A).input());
+        assert!(tracker.held(Key::A, 0.5));
+    }
+
+    #[test]
+    fn modifier_edges_come_from_the_previous_frame() {
+        let tracker = run(vec![frame(0.0).control()]);
+        assert!(tracker.pressed(Control::Ctrl));
+
+        let tracker = run(vec![frame(0.0).control(), frame(0.1).control()]);
+        assert!(!tracker.pressed(Control::Ctrl));
+        assert!(tracker.down(Control::Ctrl));
+
+        let tracker = run(vec![frame(0.0).control(), frame(0.1)]);
+        assert!(tracker.released(Control::Ctrl));
+        assert!(!tracker.down(Control::Ctrl));
+    }
+
+    #[test]
+    fn chord_needs_a_new_press_with_the_rest_held() {
+        let chord = [Control::Ctrl, Key::S.into()];
+        assert!(run(vec![
+            frame(0.0).control(),
+            frame(0.1).control().press(Key::S)
+        ])
+        .chord(&chord));
+        // Either part can come last
+        assert!(run(vec![
+            frame(0.0).press(Key::S),
+            frame(0.1).control().hold(Key::S)
+        ])
+        .chord(&chord));
+        // Both down in the same frame counts too
+        assert!(run(vec![frame(0.0).control().press(Key::S)]).chord(&chord));
+
+        let held = run(vec![
+            frame(0.0).control().press(Key::S),
+            frame(0.1).control().hold(Key::S),
+        ]);
+        assert!(!held.chord(&chord));
+        assert!(!run(vec![frame(0.0).press(Key::S)]).chord(&chord));
+    }
+
+    #[test]
+    fn sequence_must_be_in_order_and_within_the_time() {
+        let sequence = [Key::Up.into(), Key::Up.into(), Key::Down.into()];
+        let typed = |times: [f64; 3], keys: [Key; 3]| {
+            run(vec![
+                frame(times[0]).press(keys[0]),
+                frame(times[1]).press(keys[1]),
+                frame(times[2]).press(keys[2]),
+            ])
+        };
+
+        assert!(typed([0.0, 0.25, 0.5], [Key::Up, Key::Up, Key::Down]).sequence(&sequence, 0.5));
+        assert!(!typed([0.0, 0.25, 0.75], [Key::Up, Key::Up, Key::Down]).sequence(&sequence, 0.5));
+        assert!(!typed([0.0, 0.25, 0.5], [Key::Up, Key::Down, Key::Up]).sequence(&sequence, 0.5));
+        assert!(!typed([0.0, 0.25, 0.5], [Key::Down, Key::Up, Key::Up]).sequence(&sequence, 0.5));
+    }
+
+    #[test]
+    fn sequence_only_fires_on_the_frame_of_the_last_press() {
+        let mut tracker = run(vec![frame(0.0).press(Key::A), frame(0.1).press(Key::B)]);
+        let sequence = [Key::A.into(), Key::B.into()];
+        assert!(tracker.sequence(&sequence, 1.0));
+        tracker.update(&frame(0.2).input());
+        assert!(!tracker.sequence(&sequence, 1.0));
+    }
+
+    #[test]
+    fn double_click_needs_both_clicks_within_the_limit() {
+        let clicks = |gap: f64| run(vec![frame(1.0).click(), frame(1.0 + gap).click()]);
+        assert!(clicks(0.25).double_click(MouseButton::Left));
+        assert!(!clicks(0.35).double_click(MouseButton::Left));
+        assert!(!clicks(0.25).double_click(MouseButton::Right));
+        assert!(!run(vec![frame(1.0).click()]).double_click(MouseButton::Left));
+    }
+
+    #[test]
+    fn presses_are_forgotten_after_the_history_window() {
+        let tracker = run(vec![
+            frame(0.0).press(Key::A),
+            frame(HISTORY_SECONDS + 0.5).press(Key::B),
+        ]);
+        assert!(!tracker.sequence(&[Key::A.into(), Key::B.into()], 10.0));
+    }
+
+    #[test]
+    fn state_survives_a_save_and_restore() {
+        let tracker = run(vec![frame(0.0).press(Key::A), frame(0.25).hold(Key::A)]);
+        let mut restored: InputTracker =
+            bincode::deserialize(&bincode::serialize(&tracker).unwrap()).unwrap();
+        assert_eq!(restored.held_time(Key::A), 0.25);
+
+        restored.update(&frame(0.5).hold(Key::A).input());
+        assert!(restored.held(Key::A, 0.5));
+    }
+
+    // Every key, in the order of the WIT enum
+    const KEYS: [Key; 121] = [
+        Key::Space,
+        Key::Apostrophe,
+        Key::Comma,
+        Key::Minus,
+        Key::Period,
+        Key::Slash,
+        Key::KeyZero,
+        Key::KeyOne,
+        Key::KeyTwo,
+        Key::KeyThree,
+        Key::KeyFour,
+        Key::KeyFive,
+        Key::KeySix,
+        Key::KeySeven,
+        Key::KeyEight,
+        Key::KeyNine,
+        Key::Semicolon,
+        Key::Equal,
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::LeftBracket,
+        Key::Backslash,
+        Key::RightBracket,
+        Key::GraveAccent,
+        Key::WorldOne,
+        Key::WorldTwo,
+        Key::Escape,
+        Key::Enter,
+        Key::Tab,
+        Key::Backspace,
+        Key::Insert,
+        Key::Delete,
+        Key::Right,
+        Key::Left,
+        Key::Down,
+        Key::Up,
+        Key::PageUp,
+        Key::PageDown,
+        Key::Home,
+        Key::End,
+        Key::CapsLock,
+        Key::ScrollLock,
+        Key::NumLock,
+        Key::PrintScreen,
+        Key::Pause,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+        Key::F13,
+        Key::F14,
+        Key::F15,
+        Key::F16,
+        Key::F17,
+        Key::F18,
+        Key::F19,
+        Key::F20,
+        Key::F21,
+        Key::F22,
+        Key::F23,
+        Key::F24,
+        Key::F25,
+        Key::Kp0,
+        Key::Kp1,
+        Key::Kp2,
+        Key::Kp3,
+        Key::Kp4,
+        Key::Kp5,
+        Key::Kp6,
+        Key::Kp7,
+        Key::Kp8,
+        Key::Kp9,
+        Key::KpDecimal,
+        Key::KpDivide,
+        Key::KpMultiply,
+        Key::KpSubtract,
+        Key::KpAdd,
+        Key::KpEnter,
+        Key::KpEqual,
+        Key::LeftShift,
+        Key::LeftControl,
+        Key::LeftAlt,
+        Key::LeftSuper,
+        Key::RightShift,
+        Key::RightControl,
+        Key::RightAlt,
+        Key::RightSuper,
+        Key::Menu,
+        Key::Unknown,
+    ];
+
+    #[test]
+    fn key_list_covers_the_wit_enum() {
+        let wit = include_str!("../../wit/interface.wit");
+        let (_, cases) = wit.split_once("enum key {").unwrap();
+        let (cases, _) = cases.split_once('}').unwrap();
+        let cases = cases
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("//"))
+            .count();
+        assert_eq!(KEYS.len(), cases);
+        for (index, key) in KEYS.into_iter().enumerate() {
+            assert_eq!(key as usize, index);
+        }
+    }
+
+    #[test]
+    fn controls_have_distinct_ids() {
+        let controls: Vec<Control> = KEYS
+            .into_iter()
+            .map(Control::Key)
+            .chain([MouseButton::Left, MouseButton::Right, MouseButton::Middle].map(Control::Mouse))
+            .chain([Control::Shift, Control::Ctrl, Control::Alt, Control::Logo])
+            .collect();
+        let ids: HashSet<u16> = controls.iter().map(|control| control.id()).collect();
+        assert_eq!(ids.len(), controls.len());
+    }
+}
@@ -1,5 +1,5 @@
 wit_bindgen::generate!({
-    world: "hotreload-example",
+    world: "hotreload-game",
     path: "../wit",
 });
 
@@ -8,6 +8,8 @@ pub use colors::*;
 
 mod ui;
 
+pub mod input_tracker;
+
 mod game;
 pub use game::Game;
 
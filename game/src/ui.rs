@@ -1,5 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    example::game::types::{GameColor, Position, Size},
+    example::game::types::{
+        GameColor, ImageParams, Key, KeyboardInfo, MouseInfo, Position, Rect, Size, Transform,
+    },
     infrastructure::Screen,
     WHITE,
 };
@@ -22,14 +26,55 @@ impl From<(f32, f32)> for Size {
     }
 }
 
+impl From<(f32, f32, f32, f32)> for Rect {
+    fn from(value: (f32, f32, f32, f32)) -> Self {
+        Rect {
+            x: value.0,
+            y: value.1,
+            width: value.2,
+            height: value.3,
+        }
+    }
+}
+
+impl Default for ImageParams {
+    fn default() -> Self {
+        ImageParams {
+            size: None,
+            source: None,
+            rotation: 0.0,
+            pivot: None,
+            flip_x: false,
+            flip_y: false,
+            tint: GameColor {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+                a: 1.0,
+            },
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: Position { x: 0.0, y: 0.0 },
+            rotation: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+        }
+    }
+}
+
 pub enum TextSize {
     Title,
     Standard,
 }
 
-impl Into<f32> for TextSize {
-    fn into(self) -> f32 {
-        match self {
+impl From<TextSize> for f32 {
+    fn from(value: TextSize) -> Self {
+        match value {
             TextSize::Title => 40.0,
             TextSize::Standard => 20.0,
         }
@@ -46,6 +91,128 @@ pub trait ScreenExt {
 
 impl ScreenExt for Screen {
     fn text(&self, text: &str, position: (f32, f32), size: TextSize, color: GameColor) {
-        self.draw_text(text, (position.0, position.1).into(), size.into(), color);
+        self.draw_text(
+            text,
+            (position.0, position.1).into(),
+            size.into(),
+            color,
+            None,
+        );
     }
 }
+
+// Single line text entry, serializable so it can live in the game state and survive reloads
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TextField {
+    pub text: String,
+    pub focused: bool,
+    // Counted in chars rather than bytes
+    cursor: usize,
+}
+
+impl TextField {
+    pub fn new(text: &str) -> TextField {
+        TextField {
+            text: text.to_string(),
+            focused: false,
+            cursor: text.chars().count(),
+        }
+    }
+
+    // Clicking focuses or unfocuses the field, returns true when enter is pressed while focused
+    pub fn update(&mut self, bounds: Rect, mouse: &MouseInfo, key: &KeyboardInfo) -> bool {
+        if mouse.left.pressed {
+            self.focused = contains(bounds, mouse.position);
+        }
+        if !self.focused {
+            return false;
+        }
+
+        let typed = |k: Key| key.pressed.contains(&k) || key.repeated.contains(&k);
+        let length = self.text.chars().count();
+        self.cursor = self.cursor.min(length);
+
+        if !key.modifiers.control && !key.modifiers.logo {
+            for c in &key.chars {
+                self.text.insert(self.byte_offset(self.cursor), *c);
+                self.cursor += 1;
+            }
+        }
+        if typed(Key::Backspace) && self.cursor > 0 {
+            self.cursor -= 1;
+            self.text.remove(self.byte_offset(self.cursor));
+        }
+        if typed(Key::Delete) && self.cursor < self.text.chars().count() {
+            self.text.remove(self.byte_offset(self.cursor));
+        }
+        if typed(Key::Left) {
+            self.cursor = self.cursor.saturating_sub(1);
+        }
+        if typed(Key::Right) {
+            self.cursor = (self.cursor + 1).min(self.text.chars().count());
+        }
+        if typed(Key::Home) {
+            self.cursor = 0;
+        }
+        if typed(Key::End) {
+            self.cursor = self.text.chars().count();
+        }
+        if key.pressed.contains(&Key::Escape) {
+            self.focused = false;
+        }
+
+        key.pressed.contains(&Key::Enter) || key.pressed.contains(&Key::KpEnter)
+    }
+
+    pub fn draw(&self, screen: &Screen, bounds: Rect, color: GameColor) {
+        let size: f32 = TextSize::Standard.into();
+        let padding = (bounds.height - size) / 2.0 + size * 0.25;
+        let baseline = bounds.y + bounds.height - padding;
+        let thickness = if self.focused { 2.0 } else { 1.0 };
+
+        let (left, top) = (bounds.x, bounds.y);
+        let (right, bottom) = (bounds.x + bounds.width, bounds.y + bounds.height);
+        screen.draw_line((left, top).into(), (right, top).into(), thickness, color);
+        screen.draw_line(
+            (right, top).into(),
+            (right, bottom).into(),
+            thickness,
+            color,
+        );
+        screen.draw_line(
+            (right, bottom).into(),
+            (left, bottom).into(),
+            thickness,
+            color,
+        );
+        screen.draw_line((left, bottom).into(), (left, top).into(), thickness, color);
+
+        screen.push_clip(bounds);
+        screen.text(
+            &self.text,
+            (left + 6.0, baseline),
+            TextSize::Standard,
+            color,
+        );
+        if self.focused {
+            let before = &self.text[..self.byte_offset(self.cursor)];
+            let x = left + 6.0 + screen.measure_text(before, size, None).width;
+            screen.draw_line((x, top + 4.0).into(), (x, bottom - 4.0).into(), 1.0, color);
+        }
+        screen.pop_clip();
+    }
+
+    fn byte_offset(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(offset, _)| offset)
+    }
+}
+
+fn contains(rect: Rect, position: Position) -> bool {
+    position.x >= rect.x
+        && position.x <= rect.x + rect.width
+        && position.y >= rect.y
+        && position.y <= rect.y + rect.height
+}
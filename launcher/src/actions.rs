@@ -0,0 +1,669 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::log::{Log, LogLevel};
+
+#[cfg(feature = "hotreload")]
+use crate::hotreload::binding::example::game::types::{
+    ActionDeclaration, ActionKind, ActionState, FrameInput, GamepadButton, GamepadInfo, Key,
+    MouseButton,
+};
+
+#[cfg(not(feature = "hotreload"))]
+use game::example::game::types::{
+    ActionDeclaration, ActionKind, ActionState, FrameInput, GamepadButton, GamepadInfo, Key,
+    MouseButton,
+};
+
+// Kept in the assets directory, next to the files the game reads
+pub const BINDINGS_FILE: &str = "bindings.toml";
+
+// Wall clock rather than game time, so edits are still picked up while the game is paused
+const RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy)]
+enum PadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl PadAxis {
+    fn value(self, pad: &GamepadInfo) -> f32 {
+        match self {
+            PadAxis::LeftStickX => pad.left_stick.x,
+            PadAxis::LeftStickY => pad.left_stick.y,
+            PadAxis::RightStickX => pad.right_stick.x,
+            PadAxis::RightStickY => pad.right_stick.y,
+            PadAxis::LeftTrigger => pad.left_trigger,
+            PadAxis::RightTrigger => pad.right_trigger,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Key(Key),
+    Mouse(MouseButton),
+    PadButton(GamepadButton),
+    PadAxis(PadAxis),
+}
+
+#[derive(Debug, Clone)]
+pub struct Binding {
+    negative: bool,
+    source: Source,
+}
+
+// Names are matched ignoring case, dashes and underscores, so left-shift, left_shift and LeftShift agree
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn flag(down: bool) -> f32 {
+    if down {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Binding {
+    pub fn parse(text: &str) -> Result<Binding> {
+        let (negative, binding) = match text.strip_prefix('-') {
+            Some(binding) => (true, binding),
+            None => (false, text),
+        };
+        let (device, name) = binding
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Binding {text} is missing a device, like key:space"))?;
+
+        let name = normalize(name);
+        let source = match device {
+            "key" => Source::Key(parse_key(&name)?),
+            "mouse" => Source::Mouse(parse_mouse_button(&name)?),
+            "pad" => parse_pad(&name)?,
+            _ => bail!("Unknown device {device} in binding {text}"),
+        };
+        Ok(Binding { negative, source })
+    }
+
+    fn value(&self, input: &FrameInput) -> f32 {
+        let value = match &self.source {
+            Source::Key(key) => flag(input.key.down.contains(key)),
+            Source::Mouse(button) => flag(match button {
+                MouseButton::Left => input.mouse.left.down,
+                MouseButton::Right => input.mouse.right.down,
+                MouseButton::Middle => input.mouse.middle.down,
+            }),
+            Source::PadButton(button) => {
+                flag(input.gamepads.iter().any(|pad| pad.down.contains(button)))
+            }
+            Source::PadAxis(axis) => input.gamepads.iter().map(|pad| axis.value(pad)).sum(),
+        };
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+// Names are the WIT key names, like space, left-shift or key-one
+fn parse_key(name: &str) -> Result<Key> {
+    Ok(match name {
+        "space" => Key::Space,
+        "apostrophe" => Key::Apostrophe,
+        "comma" => Key::Comma,
+        "minus" => Key::Minus,
+        "period" => Key::Period,
+        "slash" => Key::Slash,
+        "keyzero" => Key::KeyZero,
+        "keyone" => Key::KeyOne,
+        "keytwo" => Key::KeyTwo,
+        "keythree" => Key::KeyThree,
+        "keyfour" => Key::KeyFour,
+        "keyfive" => Key::KeyFive,
+        "keysix" => Key::KeySix,
+        "keyseven" => Key::KeySeven,
+        "keyeight" => Key::KeyEight,
+        "keynine" => Key::KeyNine,
+        "semicolon" => Key::Semicolon,
+        "equal" => Key::Equal,
+        "a" => Key::A,
+        "b" => Key::B,
+        "c" => Key::C,
+        "d" => Key::D,
+        "e" => Key::E,
+        "f" => Key::F,
+        "g" => Key::G,
+        "h" => Key::H,
+        "i" => Key::I,
+        "j" => Key::J,
+        "k" => Key::K,
+        "l" => Key::L,
+        "m" => Key::M,
+        "n" => Key::N,
+        "o" => Key::O,
+        "p" => Key::P,
+        "q" => Key::Q,
+        "r" => Key::R,
+        "s" => Key::S,
+        "t" => Key::T,
+        "u" => Key::U,
+        "v" => Key::V,
+        "w" => Key::W,
+        "x" => Key::X,
+        "y" => Key::Y,
+        "z" => Key::Z,
+        "leftbracket" => Key::LeftBracket,
+        "backslash" => Key::Backslash,
+        "rightbracket" => Key::RightBracket,
+        "graveaccent" => Key::GraveAccent,
+        "worldone" => Key::WorldOne,
+        "worldtwo" => Key::WorldTwo,
+        "escape" => Key::Escape,
+        "enter" => Key::Enter,
+        "tab" => Key::Tab,
+        "backspace" => Key::Backspace,
+        "insert" => Key::Insert,
+        "delete" => Key::Delete,
+        "right" => Key::Right,
+        "left" => Key::Left,
+        "down" => Key::Down,
+        "up" => Key::Up,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "capslock" => Key::CapsLock,
+        "scrolllock" => Key::ScrollLock,
+        "numlock" => Key::NumLock,
+        "printscreen" => Key::PrintScreen,
+        "pause" => Key::Pause,
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+        "f13" => Key::F13,
+        "f14" => Key::F14,
+        "f15" => Key::F15,
+        "f16" => Key::F16,
+        "f17" => Key::F17,
+        "f18" => Key::F18,
+        "f19" => Key::F19,
+        "f20" => Key::F20,
+        "f21" => Key::F21,
+        "f22" => Key::F22,
+        "f23" => Key::F23,
+        "f24" => Key::F24,
+        "f25" => Key::F25,
+        "kp0" => Key::Kp0,
+        "kp1" => Key::Kp1,
+        "kp2" => Key::Kp2,
+        "kp3" => Key::Kp3,
+        "kp4" => Key::Kp4,
+        "kp5" => Key::Kp5,
+        "kp6" => Key::Kp6,
+        "kp7" => Key::Kp7,
+        "kp8" => Key::Kp8,
+        "kp9" => Key::Kp9,
+        "kpdecimal" => Key::KpDecimal,
+        "kpdivide" => Key::KpDivide,
+        "kpmultiply" => Key::KpMultiply,
+        "kpsubtract" => Key::KpSubtract,
+        "kpadd" => Key::KpAdd,
+        "kpenter" => Key::KpEnter,
+        "kpequal" => Key::KpEqual,
+        "leftshift" => Key::LeftShift,
+        "leftcontrol" => Key::LeftControl,
+        "leftalt" => Key::LeftAlt,
+        "leftsuper" => Key::LeftSuper,
+        "rightshift" => Key::RightShift,
+        "rightcontrol" => Key::RightControl,
+        "rightalt" => Key::RightAlt,
+        "rightsuper" => Key::RightSuper,
+        "menu" => Key::Menu,
+        _ => bail!("Unknown key {name}"),
+    })
+}
+
+fn parse_mouse_button(name: &str) -> Result<MouseButton> {
+    Ok(match name {
+        "left" => MouseButton::Left,
+        "right" => MouseButton::Right,
+        "middle" => MouseButton::Middle,
+        _ => bail!("Unknown mouse button {name}"),
+    })
+}
+
+// Triggers are read as analog axes, as they are also reported as buttons when pulled far enough
+fn parse_pad(name: &str) -> Result<Source> {
+    Ok(match name {
+        "leftstickx" => Source::PadAxis(PadAxis::LeftStickX),
+        "leftsticky" => Source::PadAxis(PadAxis::LeftStickY),
+        "rightstickx" => Source::PadAxis(PadAxis::RightStickX),
+        "rightsticky" => Source::PadAxis(PadAxis::RightStickY),
+        "lefttrigger" => Source::PadAxis(PadAxis::LeftTrigger),
+        "righttrigger" => Source::PadAxis(PadAxis::RightTrigger),
+        "south" => Source::PadButton(GamepadButton::South),
+        "east" => Source::PadButton(GamepadButton::East),
+        "north" => Source::PadButton(GamepadButton::North),
+        "west" => Source::PadButton(GamepadButton::West),
+        "leftbumper" => Source::PadButton(GamepadButton::LeftBumper),
+        "rightbumper" => Source::PadButton(GamepadButton::RightBumper),
+        "select" => Source::PadButton(GamepadButton::Select),
+        "start" => Source::PadButton(GamepadButton::Start),
+        "mode" => Source::PadButton(GamepadButton::Mode),
+        "leftthumb" => Source::PadButton(GamepadButton::LeftThumb),
+        "rightthumb" => Source::PadButton(GamepadButton::RightThumb),
+        "dpadup" => Source::PadButton(GamepadButton::DpadUp),
+        "dpaddown" => Source::PadButton(GamepadButton::DpadDown),
+        "dpadleft" => Source::PadButton(GamepadButton::DpadLeft),
+        "dpadright" => Source::PadButton(GamepadButton::DpadRight),
+        _ => bail!("Unknown gamepad control {name}"),
+    })
+}
+
+fn parse_bindings(log: &Log, name: &str, bindings: &[String]) -> Vec<Binding> {
+    bindings
+        .iter()
+        .filter_map(|binding| match Binding::parse(binding) {
+            Ok(binding) => Some(binding),
+            Err(e) => {
+                log.write(
+                    LogLevel::Warn,
+                    "input",
+                    &format!("Ignoring binding for action {name}: {e}"),
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct DeclaredAction {
+    name: String,
+    kind: ActionKind,
+    defaults: Vec<Binding>,
+}
+
+impl DeclaredAction {
+    pub fn new(declaration: ActionDeclaration, log: &Log) -> DeclaredAction {
+        DeclaredAction {
+            defaults: parse_bindings(log, &declaration.name, &declaration.default_bindings),
+            name: declaration.name,
+            kind: declaration.kind,
+        }
+    }
+}
+
+pub struct ActionMap {
+    path: PathBuf,
+    log: Log,
+    bindings: HashMap<String, Vec<Binding>>,
+    modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+    down: HashMap<String, bool>,
+}
+
+impl ActionMap {
+    pub fn new(path: impl Into<PathBuf>, log: Log) -> ActionMap {
+        ActionMap {
+            path: path.into(),
+            log,
+            bindings: HashMap::new(),
+            modified: None,
+            last_check: None,
+            down: HashMap::new(),
+        }
+    }
+
+    fn load(&self) -> Result<HashMap<String, Vec<Binding>>> {
+        let text = std::fs::read_to_string(&self.path)?;
+        let file: HashMap<String, Vec<String>> = toml::from_str(&text)?;
+        Ok(file
+            .into_iter()
+            .map(|(name, bindings)| {
+                let bindings = parse_bindings(&self.log, &name, &bindings);
+                (name, bindings)
+            })
+            .collect())
+    }
+
+    // A broken file keeps the previous bindings, so a typo while editing doesn't drop every control
+    fn reload_if_changed(&mut self) {
+        if self
+            .last_check
+            .is_some_and(|last_check| last_check.elapsed() < RELOAD_INTERVAL)
+        {
+            return;
+        }
+        self.last_check = Some(Instant::now());
+
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+
+        if modified.is_none() {
+            self.bindings.clear();
+            return;
+        }
+        match self.load() {
+            Ok(bindings) => self.bindings = bindings,
+            Err(e) => self.log.write(
+                LogLevel::Error,
+                "input",
+                &format!("Unable to load bindings from {}: {e}", self.path.display()),
+            ),
+        }
+    }
+
+    pub fn update(&mut self, actions: &[DeclaredAction], input: &FrameInput) -> Vec<ActionState> {
+        self.reload_if_changed();
+
+        actions
+            .iter()
+            .map(|action| {
+                let bindings = self.bindings.get(&action.name).unwrap_or(&action.defaults);
+                let value: f32 = bindings.iter().map(|binding| binding.value(input)).sum();
+                let value = match action.kind {
+                    ActionKind::Button => value.clamp(0.0, 1.0),
+                    ActionKind::Axis => value.clamp(-1.0, 1.0),
+                };
+
+                let down = value.abs() >= 0.5;
+                let was_down = self.down.insert(action.name.clone(), down).unwrap_or(false);
+                ActionState {
+                    name: action.name.clone(),
+                    value,
+                    pressed: down && !was_down,
+                    released: !down && was_down,
+                    down,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    #[cfg(feature = "hotreload")]
+    use crate::hotreload::binding::example::game::types::{
+        ClickInfo, KeyboardInfo, Modifiers, MouseInfo, Offset, Position, Size, StickInfo,
+        WindowInfo,
+    };
+
+    #[cfg(not(feature = "hotreload"))]
+    use game::example::game::types::{
+        ClickInfo, KeyboardInfo, Modifiers, MouseInfo, Offset, Position, Size, StickInfo,
+        WindowInfo,
+    };
+
+    fn frame(keys: &[Key], pads: &[f32]) -> FrameInput {
+        let click = ClickInfo {
+            pressed: false,
+            released: false,
+            down: false,
+        };
+        let stick = |x| StickInfo { x, y: 0.0 };
+        FrameInput {
+            time: 0.0,
+            delta_time: 1.0 / 60.0,
+            mouse: MouseInfo {
+                position: Position { x: 0.0, y: 0.0 },
+                left: click,
+                right: click,
+                middle: click,
+                delta: Offset { x: 0.0, y: 0.0 },
+                wheel: Offset { x: 0.0, y: 0.0 },
+            },
+            key: KeyboardInfo {
+                pressed: vec![],
+                released: vec![],
+                down: keys.to_vec(),
+                repeated: vec![],
+                chars: vec![],
+                modifiers: Modifiers {
+                    shift: false,
+                    control: false,
+                    alt: false,
+                    logo: false,
+                },
+            },
+            gamepads: pads
+                .iter()
+                .enumerate()
+                .map(|(id, x)| GamepadInfo {
+                    id: id as u32,
+                    name: "pad".to_string(),
+                    pressed: vec![],
+                    released: vec![],
+                    down: vec![],
+                    left_stick: stick(*x),
+                    right_stick: stick(0.0),
+                    left_trigger: 0.0,
+                    right_trigger: 0.0,
+                })
+                .collect(),
+            events: None,
+            actions: vec![],
+            window: WindowInfo {
+                size: Size {
+                    width: 800.0,
+                    height: 600.0,
+                },
+                events: vec![],
+            },
+        }
+    }
+
+    fn action(name: &str, kind: ActionKind, bindings: &[&str]) -> DeclaredAction {
+        DeclaredAction::new(
+            ActionDeclaration {
+                name: name.to_string(),
+                kind,
+                default_bindings: bindings.iter().map(|binding| binding.to_string()).collect(),
+            },
+            &Log::default(),
+        )
+    }
+
+    fn value(map: &mut ActionMap, action: &DeclaredAction, input: &FrameInput) -> f32 {
+        map.update(std::slice::from_ref(action), input)[0].value
+    }
+
+    // Writes the bindings file with a newer modified time, and lets the next update check it
+    fn edit(map: &mut ActionMap, text: &str) {
+        std::fs::write(&map.path, text).unwrap();
+        let modified = map.modified.unwrap_or(SystemTime::now()) + Duration::from_secs(1);
+        std::fs::File::options()
+            .write(true)
+            .open(&map.path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        map.last_check = None;
+    }
+
+    fn bindings_dir(name: &str) -> (TempDir, ActionMap, Log) {
+        let dir = TempDir::new(name);
+        std::fs::create_dir_all(dir.path()).unwrap();
+        let log = Log::default();
+        let map = ActionMap::new(dir.path().join(BINDINGS_FILE), log.clone());
+        (dir, map, log)
+    }
+
+    #[test]
+    fn key_names_follow_the_wit_enum() {
+        for (text, key) in [
+            ("key:space", Key::Space),
+            ("key:left-shift", Key::LeftShift),
+            ("key:Left_Shift", Key::LeftShift),
+            ("key:key-one", Key::KeyOne),
+            ("key:kp-enter", Key::KpEnter),
+        ] {
+            let binding = Binding::parse(text).unwrap();
+            assert!(
+                matches!(binding.source, Source::Key(parsed) if parsed == key),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        for text in [
+            "key:spcae",
+            "key:unknown",
+            "mouse:back",
+            "pad:triangle",
+            "joystick:x",
+        ] {
+            assert!(Binding::parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn leading_dash_makes_a_binding_negative() {
+        assert!(Binding::parse("-key:a").unwrap().negative);
+        assert!(!Binding::parse("key:a").unwrap().negative);
+    }
+
+    #[test]
+    fn axis_bindings_sum_and_clamp() {
+        let (_dir, mut map, _) = bindings_dir("actions-axis");
+        let walk = action(
+            "walk",
+            ActionKind::Axis,
+            &["key:d", "-key:a", "pad:left-stick-x"],
+        );
+
+        assert_eq!(value(&mut map, &walk, &frame(&[Key::D], &[])), 1.0);
+        assert_eq!(value(&mut map, &walk, &frame(&[Key::A], &[])), -1.0);
+        assert_eq!(value(&mut map, &walk, &frame(&[Key::A, Key::D], &[])), 0.0);
+        assert_eq!(value(&mut map, &walk, &frame(&[Key::A], &[0.25])), -0.75);
+        assert_eq!(value(&mut map, &walk, &frame(&[Key::D], &[0.5])), 1.0);
+        // Every connected pad adds to the axis
+        assert_eq!(value(&mut map, &walk, &frame(&[], &[-0.75, -0.75])), -1.0);
+    }
+
+    #[test]
+    fn buttons_never_go_negative() {
+        let (_dir, mut map, _) = bindings_dir("actions-button");
+        let jump = action("jump", ActionKind::Button, &["key:space", "-key:a"]);
+
+        assert_eq!(value(&mut map, &jump, &frame(&[Key::A], &[])), 0.0);
+        assert_eq!(value(&mut map, &jump, &frame(&[Key::Space], &[])), 1.0);
+        assert_eq!(value(&mut map, &jump, &frame(&[Key::Space], &[0.9])), 1.0);
+    }
+
+    #[test]
+    fn pressed_and_released_last_one_frame() {
+        let (_dir, mut map, _) = bindings_dir("actions-edges");
+        let walk = action("walk", ActionKind::Axis, &["pad:left-stick-x"]);
+        let mut states = vec![];
+        for x in [0.0, 0.4, 0.6, -0.8, 0.2, 0.0] {
+            let state = map.update(std::slice::from_ref(&walk), &frame(&[], &[x]));
+            let state = &state[0];
+            states.push((state.pressed, state.down, state.released));
+        }
+        assert_eq!(
+            states,
+            [
+                (false, false, false),
+                (false, false, false),
+                (true, true, false),
+                // Swinging across the center in one frame stays down
+                (false, true, false),
+                (false, false, true),
+                (false, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn bindings_file_replaces_the_defaults_when_it_changes() {
+        let (_dir, mut map, _) = bindings_dir("actions-reload");
+        let jump = action("jump", ActionKind::Button, &["key:space"]);
+        let space = frame(&[Key::Space], &[]);
+        let w = frame(&[Key::W], &[]);
+        assert_eq!(value(&mut map, &jump, &space), 1.0);
+
+        edit(&mut map, "jump = [\"key:w\"]");
+        assert_eq!(value(&mut map, &jump, &space), 0.0);
+        assert_eq!(value(&mut map, &jump, &w), 1.0);
+
+        edit(&mut map, "jump = [\"key:e\", \"key:space\"]");
+        assert_eq!(value(&mut map, &jump, &w), 0.0);
+        assert_eq!(value(&mut map, &jump, &space), 1.0);
+
+        // Removing the file goes back to the defaults
+        std::fs::remove_file(&map.path).unwrap();
+        map.last_check = None;
+        assert_eq!(value(&mut map, &jump, &space), 1.0);
+        assert_eq!(value(&mut map, &jump, &w), 0.0);
+    }
+
+    #[test]
+    fn broken_bindings_file_keeps_the_previous_bindings() {
+        let (_dir, mut map, log) = bindings_dir("actions-broken");
+        let jump = action("jump", ActionKind::Button, &["key:space"]);
+        let w = frame(&[Key::W], &[]);
+        edit(&mut map, "jump = [\"key:w\"]");
+        assert_eq!(value(&mut map, &jump, &w), 1.0);
+
+        edit(&mut map, "jump = [\"key:e\"");
+        assert_eq!(value(&mut map, &jump, &w), 1.0);
+        let entries = log.entries();
+        assert!(
+            entries
+                .iter()
+                .any(|entry| entry.level == LogLevel::Error && entry.target == "input"),
+            "{entries:?}"
+        );
+    }
+
+    #[test]
+    fn unknown_bindings_in_the_file_are_skipped_and_logged() {
+        let (_dir, mut map, log) = bindings_dir("actions-unknown");
+        let jump = action("jump", ActionKind::Button, &["key:space"]);
+        edit(&mut map, "jump = [\"key:spcae\", \"key:w\"]");
+
+        assert_eq!(value(&mut map, &jump, &frame(&[Key::W], &[])), 1.0);
+        let entries = log.entries();
+        assert!(
+            entries
+                .iter()
+                .any(|entry| entry.level == LogLevel::Warn && entry.message.contains("spcae")),
+            "{entries:?}"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::{Component, Path, PathBuf};
+
+pub const ASSETS_DIR: &str = "resources";
+
+// Read-only view of the assets directory, shared by both modes so guest paths resolve the same way
+#[derive(Clone)]
+pub struct Assets {
+    root: PathBuf,
+}
+
+impl Assets {
+    pub fn new(root: impl Into<PathBuf>) -> Assets {
+        Assets { root: root.into() }
+    }
+
+    // Launcher files kept with the assets, like the bindings file, which the guest doesn't name
+    pub fn file(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+
+    // Only plain names are allowed, so a guest can't reach outside the directory with .. or a root
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let relative = Path::new(path);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(format!(
+                "Asset path {path} must stay inside the assets directory"
+            ));
+        }
+        Ok(self.root.join(relative))
+    }
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, String> {
+        std::fs::read(self.resolve(path)?).map_err(|e| format!("Unable to read asset {path}: {e}"))
+    }
+
+    pub fn read_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        let entries = std::fs::read_dir(self.resolve(path)?)
+            .map_err(|e| format!("Unable to list assets in {path}: {e}"))?;
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if entry.path().is_dir() {
+                    format!("{name}/")
+                } else {
+                    name
+                }
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(path: &str) -> Result<PathBuf, String> {
+        Assets::new("resources").resolve(path)
+    }
+
+    #[test]
+    fn plain_paths_stay_inside_the_root() {
+        assert_eq!(
+            resolve("levels/one.txt"),
+            Ok(PathBuf::from("resources/levels/one.txt"))
+        );
+        assert_eq!(resolve("./one.txt"), Ok(PathBuf::from("resources/one.txt")));
+        assert_eq!(resolve(""), Ok(PathBuf::from("resources")));
+    }
+
+    #[test]
+    fn parent_components_are_rejected() {
+        for path in ["..", "../secret", "levels/../../secret", "levels/.."] {
+            assert!(resolve(path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn absolute_paths_are_rejected() {
+        for path in ["/etc/passwd", "//server/share"] {
+            assert!(resolve(path).is_err(), "{path}");
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn prefixed_paths_are_rejected() {
+        for path in ["C:\\Windows", "C:relative", "\\\\server\\share\\file"] {
+            assert!(resolve(path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn rejected_paths_are_never_read() {
+        let error = Assets::new("resources").read("../Cargo.toml").unwrap_err();
+        assert!(error.contains("inside the assets directory"), "{error}");
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use macroquad::audio::{
+    load_sound, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound,
+};
+
+use crate::log::{Log, LogLevel};
+
+#[cfg(not(feature = "hotreload"))]
+use game::example::game::host_audio::PlayParams;
+
+#[cfg(feature = "hotreload")]
+use crate::hotreload::binding::example::game::host_audio::PlayParams;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioEvent {
+    Play {
+        path: String,
+        volume: f32,
+        pan: f32,
+        looped: bool,
+    },
+    Stop {
+        path: String,
+    },
+    SetVolume {
+        path: String,
+        volume: f32,
+    },
+    PlayMusic {
+        path: String,
+        volume: f32,
+    },
+    StopMusic,
+    SetMusicVolume {
+        volume: f32,
+    },
+}
+
+impl AudioEvent {
+    pub fn play(path: &str, params: PlayParams) -> AudioEvent {
+        AudioEvent::Play {
+            path: path.to_string(),
+            volume: params.volume,
+            pan: params.pan,
+            looped: params.looped,
+        }
+    }
+}
+
+#[async_trait]
+pub trait AudioBackend: Send {
+    async fn apply(&mut self, event: AudioEvent);
+}
+
+// Guest calls are queued like draws and applied at the end of the frame, as loading sounds is async
+#[derive(Clone)]
+pub struct Audio {
+    events: Arc<Mutex<Vec<AudioEvent>>>,
+    backend: Arc<async_mutex::Mutex<Box<dyn AudioBackend>>>,
+}
+
+impl Audio {
+    pub fn new(backend: impl AudioBackend + 'static) -> Audio {
+        Audio {
+            events: Arc::new(Mutex::new(vec![])),
+            backend: Arc::new(async_mutex::Mutex::new(Box::new(backend))),
+        }
+    }
+
+    pub fn queue(&self, event: AudioEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    pub async fn flush(&self) {
+        let events = std::mem::take(&mut *self.events.lock().unwrap());
+        let mut backend = self.backend.lock().await;
+        for event in events {
+            backend.apply(event).await;
+        }
+    }
+}
+
+// Macroquad has no panning, so pan is ignored, and music is fully loaded rather than streamed
+#[derive(Default)]
+pub struct MacroquadAudio {
+    sounds: HashMap<String, Option<Sound>>,
+    music: Option<(String, Sound)>,
+}
+
+impl MacroquadAudio {
+    async fn get(&mut self, path: &str) -> Option<Sound> {
+        if let Some(sound) = self.sounds.get(path) {
+            return sound.clone();
+        }
+
+        let sound = match load_sound(path).await {
+            Ok(sound) => Some(sound),
+            Err(e) => {
+                println!("Unable to load sound {path}: {e:?}");
+                None
+            }
+        };
+        self.sounds.insert(path.to_string(), sound.clone());
+        sound
+    }
+}
+
+#[async_trait]
+impl AudioBackend for MacroquadAudio {
+    async fn apply(&mut self, event: AudioEvent) {
+        match event {
+            AudioEvent::Play {
+                path,
+                volume,
+                looped,
+                ..
+            } => {
+                if let Some(sound) = self.get(&path).await {
+                    play_sound(&sound, PlaySoundParams { looped, volume });
+                }
+            }
+            AudioEvent::Stop { path } => {
+                if let Some(sound) = self.get(&path).await {
+                    stop_sound(&sound);
+                }
+            }
+            AudioEvent::SetVolume { path, volume } => {
+                if let Some(sound) = self.get(&path).await {
+                    set_sound_volume(&sound, volume);
+                }
+            }
+            AudioEvent::PlayMusic { path, volume } => {
+                // Asking for the current track again only changes its volume, so restored games
+                // can start their music without it restarting on every reload
+                if let Some((current, music)) = &self.music {
+                    if *current == path {
+                        set_sound_volume(music, volume);
+                        return;
+                    }
+                    stop_sound(music);
+                }
+
+                self.music = self.get(&path).await.map(|music| {
+                    play_sound(
+                        &music,
+                        PlaySoundParams {
+                            looped: true,
+                            volume,
+                        },
+                    );
+                    (path, music)
+                });
+            }
+            AudioEvent::StopMusic => {
+                if let Some((_, music)) = self.music.take() {
+                    stop_sound(&music);
+                }
+            }
+            AudioEvent::SetMusicVolume { volume } => {
+                if let Some((_, music)) = &self.music {
+                    set_sound_volume(music, volume);
+                }
+            }
+        }
+    }
+}
+
+// Drops every event, for instances running alongside the one being played
+#[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+pub struct MutedAudio;
+
+#[async_trait]
+impl AudioBackend for MutedAudio {
+    async fn apply(&mut self, _event: AudioEvent) {}
+}
+
+// Plays nothing, for headless runs, and instead logs and records every event
+#[derive(Clone)]
+pub struct NullAudio {
+    events: Arc<Mutex<Vec<AudioEvent>>>,
+    log: Log,
+}
+
+impl NullAudio {
+    pub fn new(log: Log) -> NullAudio {
+        NullAudio {
+            events: Arc::new(Mutex::new(vec![])),
+            log,
+        }
+    }
+
+    // Shares the record with clones, so tests can still read it after the backend is handed to Audio
+    #[cfg(test)]
+    pub fn events(&self) -> Vec<AudioEvent> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl AudioBackend for NullAudio {
+    async fn apply(&mut self, event: AudioEvent) {
+        self.log
+            .write(LogLevel::Debug, "audio", &format!("{event:?}"));
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::*;
+
+    // Nothing in the null backend waits, so polling until ready never spins for long
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn null_audio_records_events_in_order() {
+        let backend = NullAudio::new(Log::default());
+        let audio = Audio::new(backend.clone());
+
+        audio.queue(AudioEvent::play(
+            "hit.wav",
+            PlayParams {
+                volume: 0.5,
+                pan: -1.0,
+                looped: false,
+            },
+        ));
+        audio.queue(AudioEvent::play(
+            "engine.wav",
+            PlayParams {
+                volume: 1.0,
+                pan: 0.0,
+                looped: true,
+            },
+        ));
+        assert!(backend.events().is_empty(), "events wait for the flush");
+
+        block_on(audio.flush());
+        audio.queue(AudioEvent::Stop {
+            path: "engine.wav".to_string(),
+        });
+        block_on(audio.flush());
+
+        assert_eq!(
+            backend.events(),
+            vec![
+                AudioEvent::Play {
+                    path: "hit.wav".to_string(),
+                    volume: 0.5,
+                    pan: -1.0,
+                    looped: false,
+                },
+                AudioEvent::Play {
+                    path: "engine.wav".to_string(),
+                    volume: 1.0,
+                    pan: 0.0,
+                    looped: true,
+                },
+                AudioEvent::Stop {
+                    path: "engine.wav".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn null_audio_logs_events_at_debug_level() {
+        let log = Log::default();
+        block_on(NullAudio::new(log.clone()).apply(AudioEvent::StopMusic));
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].level, LogLevel::Debug);
+        assert_eq!(entries[0].target, "audio");
+        assert_eq!(entries[0].message, "StopMusic");
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::{Arc, Mutex};
+
+use macroquad::{
+    input::{is_key_pressed, KeyCode},
+    time::get_frame_time,
+};
+
+pub const PAUSE_KEY: KeyCode = KeyCode::F9;
+pub const STEP_KEY: KeyCode = KeyCode::F10;
+
+// Single steps always advance by this much, so stepping through a session is repeatable
+const STEP_SECONDS: f32 = 1.0 / 60.0;
+
+pub struct Tick {
+    pub time: f64,
+    pub delta: f32,
+    // Paused and not stepping, so the game should see no new input either
+    pub frozen: bool,
+}
+
+#[derive(Default)]
+struct ClockState {
+    time: f64,
+    paused: bool,
+}
+
+// Game time, which is what frame inputs and the guest clocks report instead of the real time
+// It only advances by the frame time while running, and by exactly one step when single stepping
+#[derive(Clone, Default)]
+pub struct VirtualClock {
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl VirtualClock {
+    // Handles the pause and step keys, then advances the time for a new frame
+    pub fn tick(&self) -> Tick {
+        let mut state = self.state.lock().unwrap();
+        if is_key_pressed(PAUSE_KEY) {
+            state.paused = !state.paused;
+        }
+
+        let stepping = state.paused && is_key_pressed(STEP_KEY);
+        let delta = if !state.paused {
+            get_frame_time()
+        } else if stepping {
+            STEP_SECONDS
+        } else {
+            0.0
+        };
+        state.time += delta as f64;
+        Tick {
+            time: state.time,
+            delta,
+            frozen: state.paused && !stepping,
+        }
+    }
+
+    #[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+    pub fn pause(&self) {
+        self.state.lock().unwrap().paused = true;
+    }
+
+    #[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+    pub fn time(&self) -> f64 {
+        self.state.lock().unwrap().time
+    }
+
+    // Replays set the time of each recorded frame, so the guest clocks match the original session
+    #[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+    pub fn set_time(&self, time: f64) {
+        self.state.lock().unwrap().time = time;
+    }
+}
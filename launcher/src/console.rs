@@ -0,0 +1,190 @@
+use macroquad::prelude::*;
+
+use crate::{
+    input::withhold_keys,
+    log::{severity, Log, LogLevel, LEVELS},
+};
+
+#[cfg(feature = "hotreload")]
+use crate::hotreload::binding::example::game::types::{FrameInput, InputEvent, Key};
+
+#[cfg(not(feature = "hotreload"))]
+use game::example::game::types::{FrameInput, InputEvent, Key};
+
+const TOGGLE_KEY: Key = Key::GraveAccent;
+const FONT_SIZE: f32 = 16.0;
+const LINE_HEIGHT: f32 = 18.0;
+// Share of the window height covered by the open console
+const HEIGHT: f32 = 0.5;
+
+fn level_color(level: LogLevel) -> Color {
+    match level {
+        LogLevel::Trace => GRAY,
+        LogLevel::Debug => LIGHTGRAY,
+        LogLevel::Info => WHITE,
+        LogLevel::Warn => YELLOW,
+        LogLevel::Error => RED,
+    }
+}
+
+// Log viewer drawn over the game, toggled with the grave accent key
+// While open it takes the keyboard and mouse wheel, typing filters by target and message,
+// tab cycles the minimum level and the wheel or page keys scroll
+pub struct Console {
+    log: Log,
+    open: bool,
+    filter: String,
+    min_level: LogLevel,
+    // Lines scrolled back from the newest entry
+    scroll: usize,
+}
+
+impl Console {
+    pub fn new(log: Log) -> Console {
+        Console {
+            log,
+            open: false,
+            filter: String::new(),
+            min_level: LogLevel::Trace,
+            scroll: 0,
+        }
+    }
+
+    fn visible_lines() -> usize {
+        ((screen_height() * HEIGHT - LINE_HEIGHT * 2.0) / LINE_HEIGHT).max(1.0) as usize
+    }
+
+    pub fn update(&mut self, input: &mut FrameInput) {
+        if input.key.pressed.contains(&TOGGLE_KEY) {
+            self.open = !self.open;
+            input.key.chars.retain(|c| *c != '`' && *c != '~');
+        }
+        if !self.open {
+            return;
+        }
+
+        let key = &input.key;
+        let typed = |key_pressed: Key| {
+            key.pressed.contains(&key_pressed) || key.repeated.contains(&key_pressed)
+        };
+        if key.pressed.contains(&Key::Escape) {
+            self.open = false;
+        }
+        if key.pressed.contains(&Key::Tab) {
+            self.min_level = LEVELS[(severity(self.min_level) + 1) % LEVELS.len()];
+        }
+        if typed(Key::Backspace) {
+            self.filter.pop();
+        }
+        self.filter.extend(key.chars.iter());
+
+        let page = Console::visible_lines();
+        if typed(Key::PageUp) {
+            self.scroll += page;
+        }
+        if typed(Key::PageDown) {
+            self.scroll = self.scroll.saturating_sub(page);
+        }
+        let wheel = input.mouse.wheel.y.round() as isize * 3;
+        self.scroll = self.scroll.saturating_add_signed(wheel);
+
+        // The game sees no keys, scrolling or actions while the console is open
+        withhold_keys(input);
+        if let Some(events) = &mut input.events {
+            events.retain(|event| {
+                !matches!(
+                    event,
+                    InputEvent::KeyDown(_)
+                        | InputEvent::KeyUp(_)
+                        | InputEvent::Character(_)
+                        | InputEvent::MouseWheel(_)
+                )
+            });
+        }
+    }
+
+    pub fn draw(&mut self) {
+        if !self.open {
+            return;
+        }
+
+        let filter = self.filter.to_lowercase();
+        let min_severity = severity(self.min_level);
+        let all = self.log.entries();
+        let total = all.len();
+        let entries: Vec<_> = all
+            .into_iter()
+            .filter(|entry| severity(entry.level) >= min_severity)
+            .filter(|entry| {
+                filter.is_empty()
+                    || entry.target.to_lowercase().contains(&filter)
+                    || entry.message.to_lowercase().contains(&filter)
+            })
+            .collect();
+
+        // Multi-line messages, like trap backtraces, are shown one line per row under their entry
+        let rows: Vec<(String, Color)> = entries
+            .iter()
+            .flat_map(|entry| {
+                let header = format!(
+                    "{:>8.2} {:<5} {}: ",
+                    entry.time,
+                    format!("{:?}", entry.level),
+                    entry.target
+                );
+                let indent = " ".repeat(header.len());
+                entry.message.lines().enumerate().map(move |(index, line)| {
+                    let prefix = if index == 0 { &header } else { &indent };
+                    (format!("{prefix}{line}"), level_color(entry.level))
+                })
+            })
+            .collect();
+
+        let lines = Console::visible_lines();
+        self.scroll = self.scroll.min(rows.len().saturating_sub(lines));
+        let end = rows.len() - self.scroll;
+        let start = end.saturating_sub(lines);
+
+        set_default_camera();
+        let width = screen_width();
+        let height = screen_height() * HEIGHT;
+        draw_rectangle(0.0, 0.0, width, height, Color::new(0.0, 0.0, 0.0, 0.85));
+
+        for (index, (text, color)) in rows[start..end].iter().enumerate() {
+            draw_text(
+                text,
+                8.0,
+                LINE_HEIGHT * (index + 1) as f32,
+                FONT_SIZE,
+                *color,
+            );
+        }
+
+        draw_line(
+            0.0,
+            height - LINE_HEIGHT,
+            width,
+            height - LINE_HEIGHT,
+            1.0,
+            GRAY,
+        );
+        draw_text(
+            &format!(
+                "Level >= {:?} (tab)  Filter: {}_  {} of {} entries{}",
+                self.min_level,
+                self.filter,
+                entries.len(),
+                total,
+                if self.scroll > 0 {
+                    format!("  scrolled back {}", self.scroll)
+                } else {
+                    String::new()
+                }
+            ),
+            8.0,
+            height - 5.0,
+            FONT_SIZE,
+            SKYBLUE,
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use game::{
+    example::game::{
+        host_audio::PlayParams,
+        host_log::LogLevel,
+        types::{ActionDeclaration, GameColor, Size},
+    },
+    HostHandle, HostInterface,
+};
+use macroquad::texture::Texture2D;
+
+use crate::{
+    audio::AudioEvent,
+    log::Log,
+    texture_cache::{create_render_target, create_texture, update_texture},
+    HostContext,
+};
+
+// Handles are only made by DirectHost, so one of another type is a launcher bug rather than a guest one
+pub fn downcast_handle<'a, T: 'static>(
+    log: &Log,
+    handle: &'a HostHandle,
+    kind: &str,
+) -> Option<&'a T> {
+    let value = handle.downcast_ref::<T>();
+    if value.is_none() {
+        log.error(&format!("Ignoring a {kind} handle of the wrong type"));
+    }
+    value
+}
+
+pub struct DirectHost {
+    host: HostContext,
+}
+
+impl DirectHost {
+    pub fn new(host: HostContext) -> DirectHost {
+        DirectHost { host }
+    }
+}
+
+impl HostInterface for DirectHost {
+    fn create_render_target(&self, size: Size) -> HostHandle {
+        Box::new(create_render_target(size.width, size.height))
+    }
+
+    fn create_texture(&self, width: u32, height: u32, pixels: &[u8]) -> HostHandle {
+        Box::new(create_texture(width, height, pixels))
+    }
+
+    fn update_texture(&self, texture: &HostHandle, pixels: &[u8]) {
+        if let Some(texture) = downcast_handle::<Texture2D>(&self.host.log, texture, "texture") {
+            update_texture(texture, pixels);
+        }
+    }
+
+    fn play_sound(&self, path: &str, params: PlayParams) {
+        self.host.audio.queue(AudioEvent::play(path, params));
+    }
+
+    fn stop_sound(&self, path: &str) {
+        self.host.audio.queue(AudioEvent::Stop {
+            path: path.to_string(),
+        });
+    }
+
+    fn set_sound_volume(&self, path: &str, volume: f32) {
+        self.host.audio.queue(AudioEvent::SetVolume {
+            path: path.to_string(),
+            volume,
+        });
+    }
+
+    fn play_music(&self, path: &str, volume: f32) {
+        self.host.audio.queue(AudioEvent::PlayMusic {
+            path: path.to_string(),
+            volume,
+        });
+    }
+
+    fn stop_music(&self) {
+        self.host.audio.queue(AudioEvent::StopMusic);
+    }
+
+    fn set_music_volume(&self, volume: f32) {
+        self.host.audio.queue(AudioEvent::SetMusicVolume { volume });
+    }
+
+    fn set_event_stream(&self, enabled: bool) {
+        self.host.input.set_event_stream(enabled);
+    }
+
+    fn declare_actions(&self, actions: &[ActionDeclaration]) {
+        self.host
+            .input
+            .declare_actions(actions.to_vec(), &self.host.log);
+    }
+
+    fn set_window_title(&self, title: &str) {
+        self.host.window.set_title(title);
+    }
+
+    fn set_fullscreen(&self, fullscreen: bool) {
+        self.host.window.set_fullscreen(fullscreen);
+    }
+
+    fn set_clear_color(&self, color: GameColor) {
+        self.host.window.set_clear_color(color);
+    }
+
+    fn resize_window(&self, size: Size) {
+        self.host.window.resize(size);
+    }
+
+    fn quit(&self) {
+        self.host.window.quit();
+    }
+
+    fn log(&self, level: LogLevel, target: &str, message: &str) {
+        self.host.log.write(level, target, message);
+    }
+
+    fn read_asset(&self, path: &str) -> Result<Vec<u8>, String> {
+        self.host.assets.read(path)
+    }
+
+    fn read_asset_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        self.host.assets.read_dir(path)
+    }
+
+    fn storage_get(&self, slot: &str) -> Result<Option<Vec<u8>>, String> {
+        self.host.storage.get(slot)
+    }
+
+    fn storage_put(&self, slot: &str, data: &[u8]) -> Result<(), String> {
+        self.host.storage.put(slot, data)
+    }
+
+    fn storage_delete(&self, slot: &str) -> Result<(), String> {
+        self.host.storage.delete(slot)
+    }
+
+    fn storage_slots(&self) -> Result<Vec<String>, String> {
+        self.host.storage.slots()
+    }
+}
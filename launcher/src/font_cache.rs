@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use macroquad::text::{load_ttf_font_from_bytes, Font};
+
+#[derive(Clone)]
+pub struct FontCache {
+    default_font: Font,
+    fonts: HashMap<String, Font>,
+}
+
+impl FontCache {
+    pub fn new(default_font: Font) -> Self {
+        Self {
+            default_font,
+            fonts: HashMap::new(),
+        }
+    }
+
+    // Loading is synchronous, unlike textures, as measuring text must answer within the guest call
+    pub fn get(&mut self, filename: Option<&str>) -> Font {
+        let Some(filename) = filename else {
+            return self.default_font.clone();
+        };
+
+        if let Some(font) = self.fonts.get(filename) {
+            font.clone()
+        } else {
+            // Cache failures as the default font so a missing file is not re-read every frame
+            let font = Self::load(filename).unwrap_or_else(|e| {
+                println!("Unable to load font {filename}: {e:?}");
+                self.default_font.clone()
+            });
+            self.fonts.insert(filename.to_string(), font.clone());
+            font
+        }
+    }
+
+    fn load(filename: &str) -> Result<Font> {
+        let bytes = std::fs::read(filename)?;
+        Ok(load_ttf_font_from_bytes(&bytes)?)
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use gilrs::{Axis, Button, EventType, Gamepad, GamepadId, Gilrs};
+
+#[cfg(feature = "hotreload")]
+use crate::hotreload::binding::example::game::types::{GamepadButton, GamepadInfo, StickInfo};
+
+#[cfg(not(feature = "hotreload"))]
+use game::example::game::types::{GamepadButton, GamepadInfo, StickInfo};
+
+// Sticks use a radial dead zone, so diagonals are not snapped to the axes
+const STICK_DEAD_ZONE: f32 = 0.15;
+const TRIGGER_DEAD_ZONE: f32 = 0.05;
+
+#[derive(Default)]
+struct ButtonEdges {
+    pressed: Vec<GamepadButton>,
+    released: Vec<GamepadButton>,
+}
+
+pub struct Gamepads {
+    // Missing when the platform has no gamepad support, which is then treated as no pads connected
+    gilrs: Option<Gilrs>,
+}
+
+impl Gamepads {
+    pub fn new() -> Gamepads {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                println!("Gamepads unavailable: {e}");
+                None
+            }
+        };
+        Gamepads { gilrs }
+    }
+
+    pub fn poll(&mut self) -> Vec<GamepadInfo> {
+        let Some(gilrs) = &mut self.gilrs else {
+            return vec![];
+        };
+
+        let mut edges: HashMap<GamepadId, ButtonEdges> = HashMap::new();
+        while let Some(event) = gilrs.next_event() {
+            let (button, pressed) = match event.event {
+                EventType::ButtonPressed(button, _) => (button, true),
+                EventType::ButtonReleased(button, _) => (button, false),
+                _ => continue,
+            };
+            let Some(button) = map_button(button) else {
+                continue;
+            };
+
+            let pad = edges.entry(event.id).or_default();
+            if pressed {
+                pad.pressed.push(button);
+            } else {
+                pad.released.push(button);
+            }
+        }
+
+        gilrs
+            .gamepads()
+            .map(|(id, gamepad)| gamepad_info(id, gamepad, edges.remove(&id).unwrap_or_default()))
+            .collect()
+    }
+}
+
+fn gamepad_info(id: GamepadId, gamepad: Gamepad, edges: ButtonEdges) -> GamepadInfo {
+    GamepadInfo {
+        id: usize::from(id) as u32,
+        name: gamepad.name().to_string(),
+        pressed: edges.pressed,
+        released: edges.released,
+        down: BUTTONS
+            .iter()
+            .filter(|button| gamepad.is_pressed(**button))
+            .filter_map(|button| map_button(*button))
+            .collect(),
+        left_stick: stick(
+            gamepad.value(Axis::LeftStickX),
+            gamepad.value(Axis::LeftStickY),
+        ),
+        right_stick: stick(
+            gamepad.value(Axis::RightStickX),
+            gamepad.value(Axis::RightStickY),
+        ),
+        left_trigger: trigger(&gamepad, Button::LeftTrigger2),
+        right_trigger: trigger(&gamepad, Button::RightTrigger2),
+    }
+}
+
+// Rescales the live range outside the dead zone back to 0.0 to 1.0, so small movements stay smooth
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value <= dead_zone {
+        0.0
+    } else {
+        ((value - dead_zone) / (1.0 - dead_zone)).min(1.0)
+    }
+}
+
+fn stick(x: f32, y: f32) -> StickInfo {
+    let length = (x * x + y * y).sqrt();
+    let scaled = apply_dead_zone(length, STICK_DEAD_ZONE);
+    if scaled == 0.0 {
+        return StickInfo { x: 0.0, y: 0.0 };
+    }
+
+    // Gilrs reports up as positive y, the guest expects screen orientation
+    StickInfo {
+        x: x / length * scaled,
+        y: -y / length * scaled,
+    }
+}
+
+fn trigger(gamepad: &Gamepad, button: Button) -> f32 {
+    let value = gamepad.button_data(button).map_or(0.0, |data| data.value());
+    apply_dead_zone(value, TRIGGER_DEAD_ZONE)
+}
+
+const BUTTONS: [Button; 17] = [
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::LeftTrigger,
+    Button::RightTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger2,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+fn map_button(button: Button) -> Option<GamepadButton> {
+    match button {
+        Button::South => Some(GamepadButton::South),
+        Button::East => Some(GamepadButton::East),
+        Button::North => Some(GamepadButton::North),
+        Button::West => Some(GamepadButton::West),
+        Button::LeftTrigger => Some(GamepadButton::LeftBumper),
+        Button::RightTrigger => Some(GamepadButton::RightBumper),
+        Button::LeftTrigger2 => Some(GamepadButton::LeftTrigger),
+        Button::RightTrigger2 => Some(GamepadButton::RightTrigger),
+        Button::Select => Some(GamepadButton::Select),
+        Button::Start => Some(GamepadButton::Start),
+        Button::Mode => Some(GamepadButton::Mode),
+        Button::LeftThumb => Some(GamepadButton::LeftThumb),
+        Button::RightThumb => Some(GamepadButton::RightThumb),
+        Button::DPadUp => Some(GamepadButton::DpadUp),
+        Button::DPadDown => Some(GamepadButton::DpadDown),
+        Button::DPadLeft => Some(GamepadButton::DpadLeft),
+        Button::DPadRight => Some(GamepadButton::DpadRight),
+        Button::C | Button::Z | Button::Unknown => None,
+    }
+}
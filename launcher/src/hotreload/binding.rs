@@ -1,28 +1,68 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
 use anyhow::Result;
 
-use example::game::types::{GameColor, Position, Size};
-use wasmtime::component::{Component, Linker, Resource, ResourceAny};
-use wasmtime::{Config, Engine, Store};
+use example::game::host_log::PanicLocation;
+use example::game::types::{
+    ActionDeclaration, FilterMode, GameCamera, GameColor, ImageParams, Position, Rect, Size,
+    Transform, VirtualResolution,
+};
+use wasmtime::component::{
+    Component, ComponentNamedList, Instance, Lift, Linker, Lower, Resource, ResourceAny, TypedFunc,
+};
+use wasmtime::{Config, Engine, Store, WasmBacktraceDetails, WasmCoreDump};
 use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiView};
 
-use exports::example::game::game_api::{GuestGameInstance, KeyboardInfo, MouseInfo};
+use exports::example::game::game_api::{FrameInput, GuestGameInstance};
 
-use super::wasm_path;
+use super::{
+    crash::{CrashRecorder, CRASH_DIR},
+    output::GuestOutput,
+    wasm_path, BuildInfo,
+};
 pub use crate::GameScreen;
+use crate::{
+    audio::AudioEvent,
+    log::LogLevel,
+    texture_cache::{create_render_target, create_texture, update_texture},
+    HostContext,
+};
+pub use macroquad::texture::{RenderTarget, Texture2D};
+
+// Host side of the guest font resource, fonts are resolved lazily by path through the FontCache
+pub struct GameFont {
+    pub path: String,
+}
+
+// Host side of the guest sound resource, sounds are loaded by path when first played
+pub struct GameSound {
+    pub path: String,
+}
 
 wasmtime::component::bindgen!({
     path: "../wit",
+    world: "hotreload-example",
     with: {
         "example:game/host-api/game-screen": GameScreen,
+        "example:game/host-api/font": GameFont,
+        "example:game/host-api/render-target": RenderTarget,
+        "example:game/host-api/texture": Texture2D,
+        "example:game/host-audio/sound": GameSound,
     },
     trappable_imports: true,
+    // Frame inputs are serialized into crash bundles
+    additional_derives: [serde::Serialize, serde::Deserialize],
 });
 
 pub struct MyState {
     pub ctx: WasiCtx,
     pub table: ResourceTable,
+    pub host: HostContext,
+    // Reported by the guest panic hook, until the trap that follows it is returned
+    pub panic: Option<String>,
 }
 
 impl WasiView for MyState {
@@ -42,11 +82,225 @@ impl MyState {
         let id = self.table.push(item)?;
         Ok(id)
     }
+
+    fn sound_path(&self, sound: &Resource<GameSound>) -> wasmtime::Result<String> {
+        debug_assert!(!sound.owned());
+        Ok(self.table.get(sound)?.path.clone())
+    }
+
+    // Panics trap as a bare unreachable instruction, so the reported message is added to the error
+    fn attach_panic<T>(&mut self, result: Result<T>) -> Result<T> {
+        result.map_err(|e| match self.panic.take() {
+            Some(panic) => e.context(panic),
+            None => e,
+        })
+    }
+
+    fn font_path(&self, font: Option<Resource<GameFont>>) -> wasmtime::Result<Option<String>> {
+        font.map(|font| {
+            debug_assert!(!font.owned());
+            Ok(self.table.get(&font)?.path.clone())
+        })
+        .transpose()
+    }
 }
 
 impl example::game::host_api::Host for MyState {}
 impl example::game::types::Host for MyState {}
 
+impl example::game::host_audio::Host for MyState {
+    fn play(
+        &mut self,
+        sound: Resource<GameSound>,
+        params: example::game::host_audio::PlayParams,
+    ) -> wasmtime::Result<()> {
+        let path = self.sound_path(&sound)?;
+        self.host.audio.queue(AudioEvent::play(&path, params));
+        Ok(())
+    }
+
+    fn stop(&mut self, sound: Resource<GameSound>) -> wasmtime::Result<()> {
+        let path = self.sound_path(&sound)?;
+        self.host.audio.queue(AudioEvent::Stop { path });
+        Ok(())
+    }
+
+    fn set_volume(&mut self, sound: Resource<GameSound>, volume: f32) -> wasmtime::Result<()> {
+        let path = self.sound_path(&sound)?;
+        self.host
+            .audio
+            .queue(AudioEvent::SetVolume { path, volume });
+        Ok(())
+    }
+
+    fn play_music(&mut self, path: String, volume: f32) -> wasmtime::Result<()> {
+        self.host
+            .audio
+            .queue(AudioEvent::PlayMusic { path, volume });
+        Ok(())
+    }
+
+    fn stop_music(&mut self) -> wasmtime::Result<()> {
+        self.host.audio.queue(AudioEvent::StopMusic);
+        Ok(())
+    }
+
+    fn set_music_volume(&mut self, volume: f32) -> wasmtime::Result<()> {
+        self.host.audio.queue(AudioEvent::SetMusicVolume { volume });
+        Ok(())
+    }
+}
+
+impl example::game::host_log::Host for MyState {
+    fn write(&mut self, level: LogLevel, target: String, message: String) -> wasmtime::Result<()> {
+        self.host.log.write(level, &target, &message);
+        Ok(())
+    }
+
+    fn report_panic(
+        &mut self,
+        message: String,
+        location: Option<PanicLocation>,
+    ) -> wasmtime::Result<()> {
+        let panic = match location {
+            Some(PanicLocation { file, line, column }) => {
+                format!("Guest panicked at {file}:{line}:{column}: {message}")
+            }
+            None => format!("Guest panicked: {message}"),
+        };
+        self.host.log.write(LogLevel::Error, "panic", &panic);
+        self.panic = Some(panic);
+        Ok(())
+    }
+}
+
+impl example::game::host_assets::Host for MyState {
+    fn read(&mut self, path: String) -> wasmtime::Result<Result<Vec<u8>, String>> {
+        Ok(self.host.assets.read(&path))
+    }
+
+    fn read_dir(&mut self, path: String) -> wasmtime::Result<Result<Vec<String>, String>> {
+        Ok(self.host.assets.read_dir(&path))
+    }
+}
+
+impl example::game::host_storage::Host for MyState {
+    fn get(&mut self, slot: String) -> wasmtime::Result<Result<Option<Vec<u8>>, String>> {
+        Ok(self.host.storage.get(&slot))
+    }
+
+    fn put(&mut self, slot: String, data: Vec<u8>) -> wasmtime::Result<Result<(), String>> {
+        Ok(self.host.storage.put(&slot, &data))
+    }
+
+    fn delete(&mut self, slot: String) -> wasmtime::Result<Result<(), String>> {
+        Ok(self.host.storage.delete(&slot))
+    }
+
+    fn slots(&mut self) -> wasmtime::Result<Result<Vec<String>, String>> {
+        Ok(self.host.storage.slots())
+    }
+}
+
+impl example::game::host_input::Host for MyState {
+    fn set_event_stream(&mut self, enabled: bool) -> wasmtime::Result<()> {
+        self.host.input.set_event_stream(enabled);
+        Ok(())
+    }
+
+    fn declare_actions(&mut self, actions: Vec<ActionDeclaration>) -> wasmtime::Result<()> {
+        self.host.input.declare_actions(actions, &self.host.log);
+        Ok(())
+    }
+}
+
+impl example::game::host_window::Host for MyState {
+    fn set_title(&mut self, title: String) -> wasmtime::Result<()> {
+        self.host.window.set_title(&title);
+        Ok(())
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) -> wasmtime::Result<()> {
+        self.host.window.set_fullscreen(fullscreen);
+        Ok(())
+    }
+
+    fn set_clear_color(&mut self, color: GameColor) -> wasmtime::Result<()> {
+        self.host.window.set_clear_color(color);
+        Ok(())
+    }
+
+    fn resize(&mut self, size: Size) -> wasmtime::Result<()> {
+        self.host.window.resize(size);
+        Ok(())
+    }
+
+    fn quit(&mut self) -> wasmtime::Result<()> {
+        self.host.window.quit();
+        Ok(())
+    }
+}
+
+impl example::game::host_audio::HostSound for MyState {
+    fn new(&mut self, path: String) -> wasmtime::Result<Resource<GameSound>> {
+        self.convert_to_resource(GameSound { path })
+    }
+
+    fn drop(&mut self, sound: Resource<GameSound>) -> wasmtime::Result<()> {
+        debug_assert!(sound.owned());
+        self.table.delete(sound)?;
+        Ok(())
+    }
+}
+
+impl example::game::host_api::HostFont for MyState {
+    fn new(&mut self, path: String) -> wasmtime::Result<Resource<GameFont>> {
+        self.convert_to_resource(GameFont { path })
+    }
+
+    fn drop(&mut self, font: Resource<GameFont>) -> wasmtime::Result<()> {
+        debug_assert!(font.owned());
+        self.table.delete(font)?;
+        Ok(())
+    }
+}
+
+impl example::game::host_api::HostRenderTarget for MyState {
+    fn new(&mut self, size: Size) -> wasmtime::Result<Resource<RenderTarget>> {
+        self.convert_to_resource(create_render_target(size.width, size.height))
+    }
+
+    fn drop(&mut self, target: Resource<RenderTarget>) -> wasmtime::Result<()> {
+        debug_assert!(target.owned());
+        self.table.delete(target)?;
+        Ok(())
+    }
+}
+
+impl example::game::host_api::HostTexture for MyState {
+    fn new(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    ) -> wasmtime::Result<Resource<Texture2D>> {
+        self.convert_to_resource(create_texture(width, height, &pixels))
+    }
+
+    fn update(&mut self, texture: Resource<Texture2D>, pixels: Vec<u8>) -> wasmtime::Result<()> {
+        debug_assert!(!texture.owned());
+        let texture = self.table.get(&texture)?;
+        update_texture(texture, &pixels);
+        Ok(())
+    }
+
+    fn drop(&mut self, texture: Resource<Texture2D>) -> wasmtime::Result<()> {
+        debug_assert!(texture.owned());
+        self.table.delete(texture)?;
+        Ok(())
+    }
+}
+
 impl example::game::host_api::HostGameScreen for MyState {
     fn draw_text(
         &mut self,
@@ -55,13 +309,28 @@ impl example::game::host_api::HostGameScreen for MyState {
         position: Position,
         size: f32,
         color: GameColor,
+        font: Option<Resource<GameFont>>,
     ) -> wasmtime::Result<()> {
         debug_assert!(!screen.owned());
+        let font = self.font_path(font)?;
         let screen = self.table.get(&screen)?;
-        screen.draw_text(&text, position, size, color);
+        screen.draw_text(&text, position, size, color, font.as_deref());
         Ok(())
     }
 
+    fn measure_text(
+        &mut self,
+        screen: Resource<GameScreen>,
+        text: String,
+        size: f32,
+        font: Option<Resource<GameFont>>,
+    ) -> wasmtime::Result<Size> {
+        debug_assert!(!screen.owned());
+        let font = self.font_path(font)?;
+        let screen = self.table.get(&screen)?;
+        Ok(screen.measure_text(&text, size, font.as_deref()))
+    }
+
     fn draw_image(
         &mut self,
         screen: Resource<GameScreen>,
@@ -75,6 +344,19 @@ impl example::game::host_api::HostGameScreen for MyState {
         Ok(())
     }
 
+    fn draw_image_ex(
+        &mut self,
+        screen: Resource<GameScreen>,
+        filename: String,
+        position: Position,
+        params: ImageParams,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.draw_image_ex(&filename, position, params);
+        Ok(())
+    }
+
     fn draw_line(
         &mut self,
         screen: Resource<GameScreen>,
@@ -89,6 +371,173 @@ impl example::game::host_api::HostGameScreen for MyState {
         Ok(())
     }
 
+    fn push_transform(
+        &mut self,
+        screen: Resource<GameScreen>,
+        transform: Transform,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.push_transform(transform);
+        Ok(())
+    }
+
+    fn pop_transform(&mut self, screen: Resource<GameScreen>) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.pop_transform();
+        Ok(())
+    }
+
+    fn set_camera(
+        &mut self,
+        screen: Resource<GameScreen>,
+        camera: Option<GameCamera>,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.set_camera(camera);
+        Ok(())
+    }
+
+    fn world_to_screen(
+        &mut self,
+        screen: Resource<GameScreen>,
+        position: Position,
+    ) -> wasmtime::Result<Position> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        Ok(screen.world_to_screen(position))
+    }
+
+    fn screen_to_world(
+        &mut self,
+        screen: Resource<GameScreen>,
+        position: Position,
+    ) -> wasmtime::Result<Position> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        Ok(screen.screen_to_world(position))
+    }
+
+    fn push_clip(&mut self, screen: Resource<GameScreen>, rect: Rect) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.push_clip(rect);
+        Ok(())
+    }
+
+    fn pop_clip(&mut self, screen: Resource<GameScreen>) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.pop_clip();
+        Ok(())
+    }
+
+    fn draw_render_target(
+        &mut self,
+        screen: Resource<GameScreen>,
+        target: Resource<RenderTarget>,
+        position: Position,
+        params: ImageParams,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        debug_assert!(!target.owned());
+        let target = self.table.get(&target)?;
+        let screen = self.table.get(&screen)?;
+        screen.draw_render_target(target, position, params);
+        Ok(())
+    }
+
+    fn draw_texture(
+        &mut self,
+        screen: Resource<GameScreen>,
+        texture: Resource<Texture2D>,
+        position: Position,
+        params: ImageParams,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        debug_assert!(!texture.owned());
+        let texture = self.table.get(&texture)?;
+        let screen = self.table.get(&screen)?;
+        screen.draw_texture(texture, position, params);
+        Ok(())
+    }
+
+    fn clear(&mut self, screen: Resource<GameScreen>, color: GameColor) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.clear(color);
+        Ok(())
+    }
+
+    fn set_virtual_resolution(
+        &mut self,
+        screen: Resource<GameScreen>,
+        resolution: Option<VirtualResolution>,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.set_virtual_resolution(resolution);
+        Ok(())
+    }
+
+    fn set_cursor_visible(
+        &mut self,
+        screen: Resource<GameScreen>,
+        visible: bool,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.set_cursor_visible(visible);
+        Ok(())
+    }
+
+    fn set_cursor_grab(
+        &mut self,
+        screen: Resource<GameScreen>,
+        grab: bool,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.set_cursor_grab(grab);
+        Ok(())
+    }
+
+    fn present_framebuffer(
+        &mut self,
+        screen: Resource<GameScreen>,
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+        filter: FilterMode,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.present_framebuffer(width, height, &pixels, filter);
+        Ok(())
+    }
+
+    fn push_render_target(
+        &mut self,
+        screen: Resource<GameScreen>,
+        target: Resource<RenderTarget>,
+    ) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        debug_assert!(!target.owned());
+        let target = self.table.get(&target)?;
+        let screen = self.table.get(&screen)?;
+        screen.push_render_target(target);
+        Ok(())
+    }
+
+    fn pop_render_target(&mut self, screen: Resource<GameScreen>) -> wasmtime::Result<()> {
+        debug_assert!(!screen.owned());
+        let screen = self.table.get(&screen)?;
+        screen.pop_render_target();
+        Ok(())
+    }
+
     fn drop(&mut self, screen: Resource<GameScreen>) -> wasmtime::Result<()> {
         debug_assert!(screen.owned());
         self.table.delete(screen)?;
@@ -99,61 +548,155 @@ impl example::game::host_api::HostGameScreen for MyState {
 pub struct WebAssemblyContext {
     store: Store<MyState>,
     engine: Engine,
+    // The loaded component file, kept for crash bundles as the file on disk may be rebuilt since
+    component: Vec<u8>,
+    recorder: Option<CrashRecorder>,
+    write_crash_bundles: bool,
 }
 
 impl WebAssemblyContext {
-    pub fn load() -> Result<WebAssemblyContext> {
+    pub fn load(host: HostContext) -> Result<WebAssemblyContext> {
         let mut config = Config::new();
         config.wasm_component_model(true);
+        // Debug builds of the game keep their DWARF info, which resolves trap backtraces to Rust
+        // functions, files and lines, and lets a native debugger step through the guest
+        config.debug_info(true);
+        config.wasm_backtrace_details(WasmBacktraceDetails::Enable);
+        config.coredump_on_trap(true);
 
         let engine = Engine::new(&config)?;
 
+        // Randomness and time come from the launcher, so input replays behave exactly the same
         let mut wasi = WasiCtxBuilder::new();
+        wasi.secure_random(host.random.clone())
+            .insecure_random(host.random.clone())
+            .insecure_random_seed(host.random.state() as u128)
+            .monotonic_clock(host.clock.clone())
+            .wall_clock(host.clock.clone());
+        wasi.stdout(GuestOutput::new(host.log.clone(), LogLevel::Info, "stdout"))
+            .stderr(GuestOutput::new(
+                host.log.clone(),
+                LogLevel::Error,
+                "stderr",
+            ));
 
         let store = Store::new(
             &engine,
             MyState {
                 ctx: wasi.build(),
                 table: ResourceTable::new(),
+                host,
+                panic: None,
             },
         );
-        Ok(Self { store, engine })
+        Ok(Self {
+            store,
+            engine,
+            component: vec![],
+            recorder: Some(CrashRecorder::default()),
+            write_crash_bundles: true,
+        })
+    }
+
+    // Replays don't write crash bundles of their own
+    pub fn disable_crash_bundles(&mut self) {
+        self.recorder = None;
+    }
+
+    // Keeps recording, so save() is called on the same frames as for a shown instance, but never
+    // writes the bundle
+    pub fn discard_crash_bundles(&mut self) {
+        self.write_crash_bundles = false;
+    }
+}
+
+const LIFECYCLE_INTERFACE: &str = "example:game/game-lifecycle";
+const DEBUG_INTERFACE: &str = "example:game/game-debug";
+
+// Hooks from the optional game-lifecycle export
+#[derive(Clone, Copy)]
+struct Lifecycle {
+    before_reload: TypedFunc<(ResourceAny,), ()>,
+    after_reload: TypedFunc<(ResourceAny, BuildInfo), ()>,
+    exit: TypedFunc<(ResourceAny,), ()>,
+}
+
+impl Lifecycle {
+    // None when the guest doesn't export the interface, builds from before it still load
+    fn find(store: &mut Store<MyState>, instance: &Instance) -> Result<Option<Lifecycle>> {
+        let mut exports = instance.exports(store);
+        let Some(mut lifecycle) = exports.instance(LIFECYCLE_INTERFACE) else {
+            return Ok(None);
+        };
+        Ok(Some(Lifecycle {
+            before_reload: lifecycle.typed_func("on-before-reload")?,
+            after_reload: lifecycle.typed_func("on-after-reload")?,
+            exit: lifecycle.typed_func("on-exit")?,
+        }))
     }
 }
 
+type DebugState = TypedFunc<(ResourceAny,), (String,)>;
+
+// From the optional game-debug export, None when the guest doesn't have it
+fn find_debug_state(store: &mut Store<MyState>, instance: &Instance) -> Result<Option<DebugState>> {
+    let mut exports = instance.exports(store);
+    let Some(mut debug) = exports.instance(DEBUG_INTERFACE) else {
+        return Ok(None);
+    };
+    Ok(Some(debug.typed_func("debug-state")?))
+}
+
 pub struct WebAssemblyInstance {
     bindings: HotreloadExample,
+    lifecycle: Option<Lifecycle>,
+    debug_state: Option<DebugState>,
     context: Arc<Mutex<WebAssemblyContext>>,
 }
 
 impl WebAssemblyInstance {
-    pub fn load(mut context: WebAssemblyContext) -> Result<WebAssemblyInstance> {
-        let wasm_path = wasm_path()?;
+    pub fn load(context: WebAssemblyContext) -> Result<WebAssemblyInstance> {
+        let component = std::fs::read(wasm_path()?)?;
+        WebAssemblyInstance::from_component(context, component)
+    }
 
-        let component = Component::from_file(&context.engine, wasm_path)?;
+    pub fn from_component(
+        mut context: WebAssemblyContext,
+        bytes: Vec<u8>,
+    ) -> Result<WebAssemblyInstance> {
+        let component = Component::new(&context.engine, &bytes)?;
+        context.component = bytes;
 
         let mut linker = Linker::new(&context.engine);
         HotreloadExample::add_to_linker(&mut linker, |state: &mut MyState| state)?;
         wasmtime_wasi::add_to_linker_sync(&mut linker)?;
 
-        let (bindings, _) = HotreloadExample::instantiate(&mut context.store, &component, &linker)?;
+        let (bindings, instance) =
+            HotreloadExample::instantiate(&mut context.store, &component, &linker)?;
+        let lifecycle = Lifecycle::find(&mut context.store, &instance)?;
+        let debug_state = find_debug_state(&mut context.store, &instance)?;
         Ok(Self {
             bindings,
+            lifecycle,
+            debug_state,
             context: Arc::new(Mutex::new(context)),
         })
     }
 
-    pub fn create_game_instance(&mut self) -> Result<GameInstance> {
+    pub fn create_game_instance(&mut self) -> Result<GameInstance<'_>> {
         let instance_type = self.bindings.example_game_game_api().game_instance();
 
         let instance = {
             let mut context = self.context.lock().unwrap();
-            instance_type.call_constructor(&mut context.store)?
+            let result = instance_type.call_constructor(&mut context.store);
+            context.store.data_mut().attach_panic(result)?
         };
 
         Ok(GameInstance {
             instance_type,
             instance,
+            lifecycle: self.lifecycle,
+            debug_state: self.debug_state,
             context: self.context.clone(),
         })
     }
@@ -162,38 +705,148 @@ impl WebAssemblyInstance {
 pub struct GameInstance<'a> {
     instance_type: GuestGameInstance<'a>,
     instance: ResourceAny,
+    lifecycle: Option<Lifecycle>,
+    debug_state: Option<DebugState>,
     context: Arc<Mutex<WebAssemblyContext>>,
 }
 
 impl GameInstance<'_> {
-    pub fn run_frame(&self, mouse: MouseInfo, key: KeyboardInfo, screen: GameScreen) -> Result<()> {
+    fn call<T>(
+        &self,
+        call: impl FnOnce(&mut Store<MyState>, ResourceAny) -> Result<T>,
+    ) -> Result<T> {
         let mut context = self.context.lock().unwrap();
-        let screen = context.store.data_mut().convert_to_resource(screen)?;
+        let result = call(&mut context.store, self.instance);
+        context.store.data_mut().attach_panic(result)
+    }
 
-        self.instance_type
-            .call_run_frame(&mut context.store, self.instance, mouse, &key, screen)
+    pub fn run_frame(&self, input: FrameInput, screen: GameScreen) -> Result<()> {
+        self.call(|store, instance| {
+            store.data().host.clock.set_time(input.time);
+            let screen = store.data_mut().convert_to_resource(screen)?;
+            self.instance_type
+                .call_run_frame(store, instance, &input, screen)
+        })
     }
 
     pub fn save(&self) -> Result<Vec<u8>> {
-        let mut context = self.context.lock().unwrap();
-
-        self.instance_type
-            .call_save(&mut context.store, self.instance)
+        self.call(|store, instance| self.instance_type.call_save(store, instance))
     }
 
     pub fn load(&self, data: Vec<u8>) -> Result<()> {
-        let mut context = self.context.lock().unwrap();
+        self.call(|store, instance| self.instance_type.call_restore(store, instance, &data))
+    }
+
+    // Calls an export found by name rather than through the generated bindings
+    fn call_func<P, R>(&self, func: TypedFunc<P, R>, params: P) -> Result<R>
+    where
+        P: ComponentNamedList + Lower,
+        R: ComponentNamedList + Lift,
+    {
+        self.call(|store, _| {
+            let result = func.call(&mut *store, params)?;
+            func.post_return(store)?;
+            Ok(result)
+        })
+    }
+
+    // None when the guest doesn't export game-debug
+    pub fn debug_state(&self) -> Result<Option<String>> {
+        let Some(func) = self.debug_state else {
+            return Ok(None);
+        };
+        let (state,) = self.call_func(func, (self.instance,))?;
+        Ok(Some(state))
+    }
+
+    // The lifecycle hooks do nothing for guests without the game-lifecycle export
+    fn call_hook<P: ComponentNamedList + Lower>(
+        &self,
+        hook: impl FnOnce(&Lifecycle) -> TypedFunc<P, ()>,
+        params: P,
+    ) -> Result<()> {
+        match &self.lifecycle {
+            Some(lifecycle) => self.call_func(hook(lifecycle), params),
+            None => Ok(()),
+        }
+    }
+
+    pub fn on_before_reload(&self) -> Result<()> {
+        self.call_hook(|lifecycle| lifecycle.before_reload, (self.instance,))
+    }
+
+    pub fn on_after_reload(&self, previous: BuildInfo) -> Result<()> {
+        self.call_hook(
+            |lifecycle| lifecycle.after_reload,
+            (self.instance, previous),
+        )
+    }
 
-        self.instance_type
-            .call_restore(&mut context.store, self.instance, &data)
+    pub fn on_exit(&self) -> Result<()> {
+        self.call_hook(|lifecycle| lifecycle.exit, (self.instance,))
     }
 }
 
 #[async_trait::async_trait]
 impl crate::RunnableGameInstance for GameInstance<'_> {
-    fn run_frame(&self, mouse: MouseInfo, key: KeyboardInfo, screen: GameScreen) {
-        if let Err(e) = GameInstance::run_frame(self, mouse, key, screen) {
-            println!("Error running frame: {e:?}");
+    fn run_frame(&self, input: FrameInput, screen: GameScreen) {
+        if let Err(e) = self.run_recorded_frame(input, screen) {
+            self.report(e);
+        }
+    }
+}
+
+impl GameInstance<'_> {
+    // Runs the frame after adding it to the crash recording
+    pub fn run_recorded_frame(&self, input: FrameInput, screen: GameScreen) -> Result<()> {
+        self.record(&input);
+        GameInstance::run_frame(self, input, screen)
+    }
+
+    fn record(&self, input: &FrameInput) {
+        let needs_snapshot = {
+            let context = self.context.lock().unwrap();
+            let Some(recorder) = &context.recorder else {
+                return;
+            };
+            recorder.needs_snapshot()
+        };
+        // A failed save keeps the previous snapshot and the inputs since, so the bundle still replays
+        if needs_snapshot {
+            if let Ok(save) = self.save() {
+                let mut context = self.context.lock().unwrap();
+                let random = context.store.data().host.random.state();
+                if let Some(recorder) = &mut context.recorder {
+                    recorder.snapshot(save, random);
+                }
+            }
+        }
+
+        if let Some(recorder) = &mut self.context.lock().unwrap().recorder {
+            recorder.record(input.clone());
+        }
+    }
+
+    fn report(&self, error: anyhow::Error) {
+        let mut context = self.context.lock().unwrap();
+        let context = &mut *context;
+        let trap = format!("{error:?}");
+        let log = context.store.data().host.log.clone();
+        log.error(&format!("Error running frame: {trap}"));
+
+        let Some(recorder) = &mut context.recorder else {
+            return;
+        };
+        if !context.write_crash_bundles {
+            return;
+        }
+        let core_dump = error
+            .downcast_ref::<WasmCoreDump>()
+            .map(|core_dump| core_dump.serialize(&mut context.store, "game"));
+        match recorder.write(Path::new(CRASH_DIR), &context.component, &trap, core_dump) {
+            Ok(Some(dir)) => log.error(&format!("Crash bundle written to {}", dir.display())),
+            Ok(None) => {}
+            Err(e) => log.error(&format!("Unable to write crash bundle: {e:?}")),
         }
     }
 }
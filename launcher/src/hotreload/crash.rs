@@ -0,0 +1,231 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+
+use super::binding::example::game::types::FrameInput;
+
+pub const CRASH_DIR: &str = "crashes";
+
+// Frames between snapshots of the game state, which bounds how many frames a bundle replays
+const SNAPSHOT_INTERVAL: usize = 300;
+
+const COMPONENT_FILE: &str = "game.wasm";
+const SAVE_FILE: &str = "save.bin";
+const RANDOM_FILE: &str = "random.bin";
+const INPUT_FILE: &str = "input.bin";
+const TRAP_FILE: &str = "trap.txt";
+const CORE_DUMP_FILE: &str = "core.wasm";
+
+// Keeps the last good save and every frame input given to the game since, so a trap can be
+// replayed from that save
+#[derive(Default)]
+pub struct CrashRecorder {
+    save: Option<Vec<u8>>,
+    // State of the guest random number generator when the save was taken
+    random: u64,
+    inputs: Vec<FrameInput>,
+    written: bool,
+}
+
+impl CrashRecorder {
+    pub fn needs_snapshot(&self) -> bool {
+        self.save.is_none() || self.inputs.len() >= SNAPSHOT_INTERVAL
+    }
+
+    pub fn snapshot(&mut self, save: Vec<u8>, random: u64) {
+        self.save = Some(save);
+        self.random = random;
+        self.inputs.clear();
+    }
+
+    pub fn record(&mut self, input: FrameInput) {
+        self.inputs.push(input);
+    }
+
+    // Only the first trap is written, later calls usually fail because the instance is already broken
+    // Bundles go in a new folder inside root, named by the time of the crash
+    pub fn write(
+        &mut self,
+        root: &Path,
+        component: &[u8],
+        trap: &str,
+        core_dump: Option<Vec<u8>>,
+    ) -> Result<Option<PathBuf>> {
+        if self.written {
+            return Ok(None);
+        }
+        self.written = true;
+
+        let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let dir = root.join(time.to_string());
+        std::fs::create_dir_all(&dir)?;
+
+        std::fs::write(dir.join(COMPONENT_FILE), component)?;
+        if let Some(save) = &self.save {
+            std::fs::write(dir.join(SAVE_FILE), save)?;
+            std::fs::write(dir.join(RANDOM_FILE), bincode::serialize(&self.random)?)?;
+        }
+        std::fs::write(dir.join(INPUT_FILE), bincode::serialize(&self.inputs)?)?;
+        std::fs::write(dir.join(TRAP_FILE), trap)?;
+        if let Some(core_dump) = core_dump {
+            std::fs::write(dir.join(CORE_DUMP_FILE), core_dump)?;
+        }
+        Ok(Some(dir))
+    }
+}
+
+// What --repro needs from a bundle, the trap and core dump are only for people to read
+pub struct CrashBundle {
+    pub component: Vec<u8>,
+    pub save: Option<Vec<u8>>,
+    pub random: Option<u64>,
+    pub inputs: Vec<FrameInput>,
+}
+
+impl CrashBundle {
+    pub fn read(dir: &Path) -> Result<CrashBundle> {
+        let component = std::fs::read(dir.join(COMPONENT_FILE))
+            .with_context(|| format!("Unable to read crash bundle {}", dir.display()))?;
+        let save_path = dir.join(SAVE_FILE);
+        let save = if save_path.exists() {
+            Some(std::fs::read(save_path)?)
+        } else {
+            None
+        };
+        let random_path = dir.join(RANDOM_FILE);
+        let random = if random_path.exists() {
+            Some(bincode::deserialize(&std::fs::read(random_path)?)?)
+        } else {
+            None
+        };
+        let inputs = bincode::deserialize(&std::fs::read(dir.join(INPUT_FILE))?)?;
+        Ok(CrashBundle {
+            component,
+            save,
+            random,
+            inputs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotreload::binding::example::game::types::{
+        ClickInfo, KeyboardInfo, Modifiers, MouseInfo, Offset, Position, Size, WindowInfo,
+    };
+    use crate::temp_dir::TempDir;
+
+    fn frame(time: f64) -> FrameInput {
+        let click = ClickInfo {
+            pressed: false,
+            released: false,
+            down: time > 1.0,
+        };
+        FrameInput {
+            time,
+            delta_time: 1.0 / 60.0,
+            mouse: MouseInfo {
+                position: Position {
+                    x: time as f32,
+                    y: 2.0,
+                },
+                left: click,
+                right: click,
+                middle: click,
+                delta: Offset { x: 0.0, y: 0.0 },
+                wheel: Offset { x: 0.0, y: 1.0 },
+            },
+            key: KeyboardInfo {
+                pressed: vec![],
+                released: vec![],
+                down: vec![],
+                repeated: vec![],
+                chars: vec!['a'],
+                modifiers: Modifiers {
+                    shift: true,
+                    control: false,
+                    alt: false,
+                    logo: false,
+                },
+            },
+            gamepads: vec![],
+            events: None,
+            actions: vec![],
+            window: WindowInfo {
+                size: Size {
+                    width: 800.0,
+                    height: 600.0,
+                },
+                events: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn bundle_round_trips_the_last_snapshot_and_inputs() {
+        let root = TempDir::new("crash-round-trip");
+        let mut recorder = CrashRecorder::default();
+        assert!(recorder.needs_snapshot());
+        recorder.snapshot(vec![1, 2, 3], 11);
+        recorder.record(frame(0.5));
+        // A newer snapshot replaces the inputs recorded before it
+        recorder.snapshot(vec![4, 5, 6], 42);
+        recorder.record(frame(1.0));
+        recorder.record(frame(1.5));
+        assert!(!recorder.needs_snapshot());
+
+        let dir = recorder
+            .write(root.path(), b"component", "trap", Some(vec![9]))
+            .unwrap()
+            .unwrap();
+        let bundle = CrashBundle::read(&dir).unwrap();
+        assert_eq!(bundle.component, b"component");
+        assert_eq!(bundle.save, Some(vec![4, 5, 6]));
+        assert_eq!(bundle.random, Some(42));
+        assert_eq!(bundle.inputs.len(), 2);
+        assert_eq!(
+            format!("{:?}", bundle.inputs),
+            format!("{:?}", [frame(1.0), frame(1.5)])
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join(TRAP_FILE)).unwrap(),
+            "trap"
+        );
+        assert_eq!(std::fs::read(dir.join(CORE_DUMP_FILE)).unwrap(), [9]);
+    }
+
+    #[test]
+    fn bundle_without_a_snapshot_has_no_save() {
+        let root = TempDir::new("crash-no-snapshot");
+        let mut recorder = CrashRecorder::default();
+        recorder.record(frame(0.0));
+
+        let dir = recorder
+            .write(root.path(), b"", "trap", None)
+            .unwrap()
+            .unwrap();
+        let bundle = CrashBundle::read(&dir).unwrap();
+        assert_eq!(bundle.save, None);
+        assert_eq!(bundle.random, None);
+        assert_eq!(bundle.inputs.len(), 1);
+        assert!(!dir.join(CORE_DUMP_FILE).exists());
+    }
+
+    #[test]
+    fn only_the_first_trap_is_written() {
+        let root = TempDir::new("crash-first-only");
+        let mut recorder = CrashRecorder::default();
+        assert!(recorder
+            .write(root.path(), b"", "first", None)
+            .unwrap()
+            .is_some());
+        assert!(recorder
+            .write(root.path(), b"", "second", None)
+            .unwrap()
+            .is_none());
+    }
+}
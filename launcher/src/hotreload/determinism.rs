@@ -0,0 +1,334 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+// Byte ranges listed when the saves can only be compared as bytes, and the bytes shown per range
+const MAX_BYTE_RANGES: usize = 16;
+const MAX_RANGE_BYTES: usize = 16;
+
+pub fn state_hash(save: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    save.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Index just after the closing quote of a string that starts the text, skipping escaped quotes
+fn quoted_end(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+// Splits a line into a field name or map key and its value, keys are plain words or quoted strings
+// so a string value or key containing ": " isn't split in the middle
+fn split_entry(text: &str) -> Option<(&str, &str)> {
+    let end = if text.starts_with('"') {
+        quoted_end(text)?
+    } else {
+        text.find(": ")?
+    };
+    let (key, value) = text.split_at(end);
+    let value = value.strip_prefix(": ")?;
+    let word = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+    (word || key.starts_with('"')).then_some((key, value))
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if key.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        format!("{parent}.{key}")
+    } else {
+        format!("{parent}[{key}]")
+    }
+}
+
+struct Parent {
+    indent: usize,
+    path: String,
+    // Items without a field name, like list entries, are named by their position
+    items: usize,
+}
+
+// Flattens pretty printed Debug output into one value per line, keyed by the path leading to it
+fn flatten(state: &str) -> Vec<(String, String)> {
+    let mut parents: Vec<Parent> = vec![];
+    let mut values = vec![];
+
+    for line in state.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let text = line.trim().trim_end_matches(',');
+        while parents.last().is_some_and(|parent| parent.indent >= indent) {
+            parents.pop();
+        }
+        if matches!(text, "}" | "]" | ")") {
+            continue;
+        }
+
+        let (path, value) = match (parents.last_mut(), split_entry(text)) {
+            (None, _) => ("state".to_string(), text),
+            (Some(parent), Some((key, value))) => (child_path(&parent.path, key), value),
+            (Some(parent), None) => {
+                parent.items += 1;
+                (format!("{}[{}]", parent.path, parent.items - 1), text)
+            }
+        };
+
+        // Openers are kept as values too, an enum can change variant while keeping its fields
+        if value.ends_with('{') || value.ends_with('[') || value.ends_with('(') {
+            parents.push(Parent {
+                indent,
+                path: path.clone(),
+                items: 0,
+            });
+        }
+        values.push((path, value.to_string()));
+    }
+    values
+}
+
+// One line per field that differs between the two dumps, as path: left != right
+pub fn diff_states(left: &str, right: &str) -> Vec<String> {
+    let left = flatten(left);
+    let right = flatten(right);
+    let right_values: HashMap<&str, &str> = right
+        .iter()
+        .map(|(path, value)| (path.as_str(), value.as_str()))
+        .collect();
+    let left_paths: HashMap<&str, &str> = left
+        .iter()
+        .map(|(path, value)| (path.as_str(), value.as_str()))
+        .collect();
+
+    let mut differences = vec![];
+    for (path, value) in &left {
+        match right_values.get(path.as_str()) {
+            Some(other) if other == value => {}
+            Some(other) => differences.push(format!("{path}: {value} != {other}")),
+            None => differences.push(format!("{path}: {value} != <missing>")),
+        }
+    }
+    for (path, value) in &right {
+        if !left_paths.contains_key(path.as_str()) {
+            differences.push(format!("{path}: <missing> != {value}"));
+        }
+    }
+    differences
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// For guests without game-debug, the byte ranges where the two saves differ, as start..end: left != right
+pub fn diff_bytes(left: &[u8], right: &[u8]) -> Vec<String> {
+    let mut differences = vec![];
+    if left.len() != right.len() {
+        differences.push(format!("length: {} != {}", left.len(), right.len()));
+    }
+
+    let shared = left.len().min(right.len());
+    let mut index = 0;
+    let mut ranges = 0;
+    while index < shared {
+        if left[index] == right[index] {
+            index += 1;
+            continue;
+        }
+        if ranges == MAX_BYTE_RANGES {
+            differences.push("...".to_string());
+            break;
+        }
+
+        let start = index;
+        while index < shared && index - start < MAX_RANGE_BYTES && left[index] != right[index] {
+            index += 1;
+        }
+        differences.push(format!(
+            "bytes {start}..{index}: {} != {}",
+            hex(&left[start..index]),
+            hex(&right[start..index])
+        ));
+        ranges += 1;
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Default)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone)]
+    enum Mode {
+        Idle,
+        Moving { speed: f32 },
+        Waiting(u32),
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone)]
+    struct State {
+        count: u32,
+        name: String,
+        player: Position,
+        items: Vec<Position>,
+        mode: Mode,
+        target: Option<Position>,
+        scores: BTreeMap<String, u32>,
+    }
+
+    fn state() -> State {
+        State {
+            count: 1,
+            name: "player".to_string(),
+            player: Position { x: 1.0, y: 2.0 },
+            items: vec![Position { x: 3.0, y: 4.0 }, Position { x: 5.0, y: 6.0 }],
+            mode: Mode::Moving { speed: 1.0 },
+            target: Some(Position::default()),
+            scores: BTreeMap::from([("alice".to_string(), 1), ("bob: the second".to_string(), 2)]),
+        }
+    }
+
+    fn diff(left: &State, right: &State) -> Vec<String> {
+        diff_states(&format!("{left:#?}"), &format!("{right:#?}"))
+    }
+
+    #[test]
+    fn equal_states_have_no_differences() {
+        assert!(diff(&state(), &state()).is_empty());
+    }
+
+    #[test]
+    fn nested_struct_fields_are_named_by_path() {
+        let mut right = state();
+        right.count = 2;
+        right.player.y = 2.5;
+        assert_eq!(
+            diff(&state(), &right),
+            ["state.count: 1 != 2", "state.player.y: 2.0 != 2.5"]
+        );
+    }
+
+    #[test]
+    fn list_items_are_named_by_position() {
+        let mut right = state();
+        right.items[1].x = 7.0;
+        right.items.push(Position { x: 8.0, y: 9.0 });
+        assert_eq!(
+            diff(&state(), &right),
+            [
+                "state.items[1].x: 5.0 != 7.0",
+                "state.items[2]: <missing> != Position {",
+                "state.items[2].x: <missing> != 8.0",
+                "state.items[2].y: <missing> != 9.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_variant_changes_are_reported() {
+        let mut right = state();
+        right.mode = Mode::Waiting(3);
+        right.target = None;
+        assert_eq!(
+            diff(&state(), &right),
+            [
+                "state.mode: Moving { != Waiting(",
+                "state.mode.speed: 1.0 != <missing>",
+                "state.target: Some( != None",
+                "state.target[0]: Position { != <missing>",
+                "state.target[0].x: 0.0 != <missing>",
+                "state.target[0].y: 0.0 != <missing>",
+                "state.mode[0]: <missing> != 3",
+            ]
+        );
+
+        let mut left = state();
+        left.mode = Mode::Idle;
+        assert_eq!(
+            diff(&left, &right)[0],
+            "state.mode: Idle != Waiting(".to_string()
+        );
+    }
+
+    #[test]
+    fn map_entries_are_named_by_their_quoted_keys() {
+        let mut right = state();
+        right.scores.insert("alice".to_string(), 5);
+        right.scores.insert("bob: the second".to_string(), 6);
+        assert_eq!(
+            diff(&state(), &right),
+            [
+                "state.scores[\"alice\"]: 1 != 5",
+                "state.scores[\"bob: the second\"]: 2 != 6",
+            ]
+        );
+    }
+
+    #[test]
+    fn string_values_containing_separators_stay_whole() {
+        let mut left = state();
+        left.name = "a: b, c".to_string();
+        let mut right = state();
+        right.name = "a: b, d \"quoted: yes\"".to_string();
+        assert_eq!(
+            diff(&left, &right),
+            ["state.name: \"a: b, c\" != \"a: b, d \\\"quoted: yes\\\"\""]
+        );
+    }
+
+    #[test]
+    fn string_list_items_are_not_taken_for_fields() {
+        let left = vec!["key: value".to_string()];
+        let right = vec!["key: other".to_string()];
+        assert_eq!(
+            diff_states(&format!("{left:#?}"), &format!("{right:#?}")),
+            ["state[0]: \"key: value\" != \"key: other\""]
+        );
+    }
+
+    #[test]
+    fn byte_diff_lists_differing_ranges() {
+        assert!(diff_bytes(&[1, 2, 3], &[1, 2, 3]).is_empty());
+        assert_eq!(
+            diff_bytes(&[1, 2, 3, 4, 5], &[1, 9, 9, 4, 6, 7]),
+            [
+                "length: 5 != 6",
+                "bytes 1..3: 02 03 != 09 09",
+                "bytes 4..5: 05 != 06",
+            ]
+        );
+    }
+
+    #[test]
+    fn byte_diff_is_bounded() {
+        let left = vec![0; 1000];
+        let right = vec![1; 1000];
+        let differences = diff_bytes(&left, &right);
+        assert_eq!(differences.len(), MAX_BYTE_RANGES + 1);
+        assert_eq!(
+            differences[0],
+            format!("bytes 0..16: {0} != {1}", hex(&[0; 16]), hex(&[1; 16]))
+        );
+        assert_eq!(differences[MAX_BYTE_RANGES], "...");
+    }
+}
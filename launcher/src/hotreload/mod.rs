@@ -1,8 +1,13 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::UNIX_EPOCH};
 
 use anyhow::Result;
+use wasmtime::component::{ComponentType, Lower};
 
 pub mod binding;
+pub mod crash;
+pub mod determinism;
+mod output;
+pub mod wasi;
 pub mod watcher;
 
 pub fn wasm_path() -> Result<PathBuf> {
@@ -11,3 +16,25 @@ pub fn wasm_path() -> Result<PathBuf> {
     wasm_path.push("game.wasm");
     Ok(wasm_path)
 }
+
+// The WIT build-info record, only used by the optional game-lifecycle interface, which isn't part
+// of the world the bindings are generated from
+#[derive(ComponentType, Lower, Clone, Copy, Debug)]
+#[component(record)]
+pub struct BuildInfo {
+    pub generation: u32,
+    #[component(name = "modified-ms")]
+    pub modified_ms: u64,
+    #[component(name = "size-bytes")]
+    pub size_bytes: u64,
+}
+
+pub fn build_info(generation: u32) -> Result<BuildInfo> {
+    let metadata = std::fs::metadata(wasm_path()?)?;
+    let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?;
+    Ok(BuildInfo {
+        generation,
+        modified_ms: modified.as_millis() as u64,
+        size_bytes: metadata.len(),
+    })
+}
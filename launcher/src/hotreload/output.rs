@@ -0,0 +1,67 @@
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use wasmtime_wasi::{HostOutputStream, StdoutStream, StreamResult, Subscribe};
+
+use crate::log::{Log, LogLevel};
+
+// Guest stdout or stderr, logged one line at a time
+#[derive(Clone)]
+pub struct GuestOutput {
+    log: Log,
+    level: LogLevel,
+    target: &'static str,
+    // The guest may take a new stream for every write, so a partial line is shared between them
+    line: Arc<Mutex<Vec<u8>>>,
+}
+
+impl GuestOutput {
+    pub fn new(log: Log, level: LogLevel, target: &'static str) -> GuestOutput {
+        GuestOutput {
+            log,
+            level,
+            target,
+            line: Arc::new(Mutex::new(vec![])),
+        }
+    }
+}
+
+impl StdoutStream for GuestOutput {
+    fn stream(&self) -> Box<dyn HostOutputStream> {
+        Box::new(self.clone())
+    }
+
+    fn isatty(&self) -> bool {
+        false
+    }
+}
+
+impl HostOutputStream for GuestOutput {
+    fn write(&mut self, bytes: Bytes) -> StreamResult<()> {
+        let mut line = self.line.lock().unwrap();
+        for byte in bytes {
+            if byte == b'\n' {
+                let text = String::from_utf8_lossy(&line);
+                self.log.write(self.level, self.target, text.trim_end());
+                line.clear();
+            } else {
+                line.push(byte);
+            }
+        }
+        Ok(())
+    }
+
+    // Partial lines are kept until their newline, so a print! split over calls stays one entry
+    fn flush(&mut self) -> StreamResult<()> {
+        Ok(())
+    }
+
+    fn check_write(&mut self) -> StreamResult<usize> {
+        Ok(usize::MAX)
+    }
+}
+
+#[async_trait::async_trait]
+impl Subscribe for GuestOutput {
+    async fn ready(&mut self) {}
+}
@@ -0,0 +1,80 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use wasmtime_wasi::{HostMonotonicClock, HostWallClock, RngCore};
+
+use crate::clock::VirtualClock;
+
+// The guest clocks follow the virtual clock, so they freeze while paused and replay exactly
+// The wall clock counts from the unix epoch rather than the real date, which would differ per run
+impl HostMonotonicClock for VirtualClock {
+    fn resolution(&self) -> u64 {
+        1
+    }
+
+    fn now(&self) -> u64 {
+        (self.time() * 1e9) as u64
+    }
+}
+
+impl HostWallClock for VirtualClock {
+    fn resolution(&self) -> Duration {
+        Duration::from_nanos(1)
+    }
+
+    fn now(&self) -> Duration {
+        Duration::from_secs_f64(self.time())
+    }
+}
+
+// SplitMix64, small and fast, and its whole state is one number that crash bundles can store
+// Shared by every reload, so the sequence carries on rather than restarting with each build
+#[derive(Clone)]
+pub struct SeededRng {
+    state: Arc<Mutex<u64>>,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        SeededRng {
+            state: Arc::new(Mutex::new(seed)),
+        }
+    }
+
+    pub fn state(&self) -> u64 {
+        *self.state.lock().unwrap()
+    }
+
+    pub fn set_state(&self, state: u64) {
+        *self.state.lock().unwrap() = state;
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        *state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
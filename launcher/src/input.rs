@@ -1,18 +1,292 @@
-use macroquad::prelude::*;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use macroquad::{input::utils, miniquad, prelude::*};
+
+use crate::{
+    actions::{ActionMap, DeclaredAction, BINDINGS_FILE},
+    clock::VirtualClock,
+    gamepad::Gamepads,
+    log::Log,
+    viewport::Viewport,
+    HostContext,
+};
 
 #[cfg(feature = "hotreload")]
 use crate::hotreload::binding::example::game::types::{
-    ClickInfo, Key, KeyboardInfo, MouseInfo, Position,
+    ActionDeclaration, ClickInfo, FrameInput, InputEvent, Key, KeyEvent, KeyboardInfo, Modifiers,
+    MouseButton as GameMouseButton, MouseInfo, Offset, Position, Size, WindowEvent, WindowInfo,
 };
 
 #[cfg(not(feature = "hotreload"))]
-pub use game::example::game::types::{ClickInfo, Key, KeyboardInfo, MouseInfo, Position};
+pub use game::example::game::types::{
+    ActionDeclaration, ClickInfo, FrameInput, InputEvent, Key, KeyEvent, KeyboardInfo, Modifiers,
+    MouseButton as GameMouseButton, MouseInfo, Offset, Position, Size, WindowEvent, WindowInfo,
+};
+
+// Guest controlled input options, shared with the host-input implementation
+#[derive(Clone, Default)]
+pub struct InputConfig {
+    event_stream: Arc<AtomicBool>,
+    actions: Arc<Mutex<Vec<DeclaredAction>>>,
+}
+
+impl InputConfig {
+    pub fn set_event_stream(&self, enabled: bool) {
+        self.event_stream.store(enabled, Ordering::Relaxed);
+    }
+
+    fn event_stream(&self) -> bool {
+        self.event_stream.load(Ordering::Relaxed)
+    }
+
+    pub fn declare_actions(&self, actions: Vec<ActionDeclaration>, log: &Log) {
+        *self.actions.lock().unwrap() = actions
+            .into_iter()
+            .map(|action| DeclaredAction::new(action, log))
+            .collect();
+    }
+
+    fn actions(&self) -> Vec<DeclaredAction> {
+        self.actions.lock().unwrap().clone()
+    }
+}
+
+// Macroquad only exposes key repeats and event order as raw miniquad events, which are replayed here
+struct EventRecorder {
+    viewport: Viewport,
+    repeated: Vec<Key>,
+    events: Vec<InputEvent>,
+}
+
+impl EventRecorder {
+    fn record(&mut self, event: InputEvent) {
+        self.events.push(event);
+    }
+}
+
+impl miniquad::EventHandler for EventRecorder {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
+
+    fn mouse_motion_event(&mut self, x: f32, y: f32) {
+        let position = self.viewport.to_virtual(vec2(x, y) / screen_dpi_scale());
+        self.record(InputEvent::MouseMove(Position {
+            x: position.x,
+            y: position.y,
+        }));
+    }
+
+    fn mouse_wheel_event(&mut self, x: f32, y: f32) {
+        self.record(InputEvent::MouseWheel(Offset { x, y }));
+    }
+
+    fn mouse_button_down_event(&mut self, button: MouseButton, _x: f32, _y: f32) {
+        if let Some(button) = map_mouse_button(button) {
+            self.record(InputEvent::MouseDown(button));
+        }
+    }
+
+    fn mouse_button_up_event(&mut self, button: MouseButton, _x: f32, _y: f32) {
+        if let Some(button) = map_mouse_button(button) {
+            self.record(InputEvent::MouseUp(button));
+        }
+    }
+
+    fn char_event(&mut self, character: char, _modifiers: miniquad::KeyMods, _repeat: bool) {
+        if !character.is_control() {
+            self.record(InputEvent::Character(character));
+        }
+    }
+
+    fn key_down_event(&mut self, keycode: KeyCode, _modifiers: miniquad::KeyMods, repeat: bool) {
+        let key = map_key(keycode);
+        if repeat {
+            self.repeated.push(key);
+        }
+        self.record(InputEvent::KeyDown(KeyEvent { key, repeat }));
+    }
+
+    fn key_up_event(&mut self, keycode: KeyCode, _modifiers: miniquad::KeyMods) {
+        self.record(InputEvent::KeyUp(map_key(keycode)));
+    }
+}
 
-pub fn get_key_info() -> KeyboardInfo {
+fn map_mouse_button(button: MouseButton) -> Option<GameMouseButton> {
+    match button {
+        MouseButton::Left => Some(GameMouseButton::Left),
+        MouseButton::Right => Some(GameMouseButton::Right),
+        MouseButton::Middle => Some(GameMouseButton::Middle),
+        MouseButton::Unknown => None,
+    }
+}
+
+pub struct Input {
+    config: InputConfig,
+    clock: VirtualClock,
+    gamepads: Gamepads,
+    actions: ActionMap,
+    subscriber: usize,
+    // Window pixels, as the virtual resolution can change between frames
+    last_mouse_position: Option<Vec2>,
+    last_window_size: Option<Vec2>,
+    frozen: bool,
+}
+
+impl Input {
+    pub fn new(host: &HostContext) -> Input {
+        Input {
+            config: host.input.clone(),
+            clock: host.clock.clone(),
+            gamepads: Gamepads::new(),
+            actions: ActionMap::new(host.assets.file(BINDINGS_FILE), host.log.clone()),
+            subscriber: utils::register_input_subscriber(),
+            last_mouse_position: None,
+            last_window_size: None,
+            frozen: false,
+        }
+    }
+
+    pub fn gather(&mut self, viewport: Viewport) -> FrameInput {
+        let tick = self.clock.tick();
+        let mut recorder = EventRecorder {
+            viewport,
+            repeated: vec![],
+            events: vec![],
+        };
+        utils::repeat_all_miniquad_input(&mut recorder, self.subscriber);
+
+        let mouse_position = Vec2::from(mouse_position());
+        let delta = mouse_position - self.last_mouse_position.unwrap_or(mouse_position);
+        self.last_mouse_position = Some(mouse_position);
+
+        let mut input = FrameInput {
+            time: tick.time,
+            delta_time: tick.delta,
+            mouse: get_mouse_state(viewport, delta / viewport.scale),
+            key: get_key_info(recorder.repeated),
+            gamepads: self.gamepads.poll(),
+            events: self.config.event_stream().then_some(recorder.events),
+            actions: vec![],
+            window: self.window_info(),
+        };
+        input.actions = self.actions.update(&self.config.actions(), &input);
+        self.frozen = tick.frozen;
+        input
+    }
+
+    // True while the clock is paused, unless this frame is a single step
+    #[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+    pub fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    // Applied after the console, so it can still be used while paused
+    pub fn freeze_if_paused(&self, input: &mut FrameInput) {
+        if self.frozen {
+            freeze(input);
+        }
+    }
+
+    // Resizes are found by comparing sizes, as macroquad doesn't forward the window events
+    fn window_info(&mut self) -> WindowInfo {
+        let window_size = vec2(screen_width(), screen_height());
+        let size = Size {
+            width: window_size.x,
+            height: window_size.y,
+        };
+
+        let mut events = vec![];
+        if self
+            .last_window_size
+            .is_some_and(|last_size| last_size != window_size)
+        {
+            events.push(WindowEvent::Resized(size));
+        }
+        self.last_window_size = Some(window_size);
+
+        WindowInfo { size, events }
+    }
+}
+
+// Hides the keyboard, scrolling and actions from the game, for when the launcher owns them
+pub fn withhold_keys(input: &mut FrameInput) {
+    let key = &mut input.key;
+    key.pressed.clear();
+    key.released.clear();
+    key.down.clear();
+    key.repeated.clear();
+    key.chars.clear();
+    input.mouse.wheel = Offset { x: 0.0, y: 0.0 };
+
+    // Actions are mostly bound to keys, so they all read as released
+    for action in &mut input.actions {
+        action.value = 0.0;
+        action.pressed = false;
+        action.released = false;
+        action.down = false;
+    }
+}
+
+// While paused the game gets frames with nothing new in them, only where the mouse and window are
+fn freeze(input: &mut FrameInput) {
+    withhold_keys(input);
+
+    let mouse = &mut input.mouse;
+    for click in [&mut mouse.left, &mut mouse.right, &mut mouse.middle] {
+        *click = ClickInfo {
+            pressed: false,
+            released: false,
+            down: false,
+        };
+    }
+    mouse.delta = Offset { x: 0.0, y: 0.0 };
+
+    for pad in &mut input.gamepads {
+        pad.pressed.clear();
+        pad.released.clear();
+        pad.down.clear();
+        pad.left_stick.x = 0.0;
+        pad.left_stick.y = 0.0;
+        pad.right_stick.x = 0.0;
+        pad.right_stick.y = 0.0;
+        pad.left_trigger = 0.0;
+        pad.right_trigger = 0.0;
+    }
+    if let Some(events) = &mut input.events {
+        events.clear();
+    }
+}
+
+pub fn get_key_info(repeated: Vec<Key>) -> KeyboardInfo {
     KeyboardInfo {
         pressed: get_keys_pressed().into_iter().map(map_key).collect(),
         released: get_keys_released().into_iter().map(map_key).collect(),
         down: get_keys_down().into_iter().map(map_key).collect(),
+        repeated,
+        chars: get_chars(),
+        modifiers: get_modifiers(),
+    }
+}
+
+// The char queue is never cleared by macroquad and pops the newest char first
+fn get_chars() -> Vec<char> {
+    let mut chars: Vec<char> = std::iter::from_fn(get_char_pressed).collect();
+    chars.reverse();
+    chars.retain(|c| !c.is_control());
+    chars
+}
+
+fn get_modifiers() -> Modifiers {
+    let either = |left, right| is_key_down(left) || is_key_down(right);
+    Modifiers {
+        shift: either(KeyCode::LeftShift, KeyCode::RightShift),
+        control: either(KeyCode::LeftControl, KeyCode::RightControl),
+        alt: either(KeyCode::LeftAlt, KeyCode::RightAlt),
+        logo: either(KeyCode::LeftSuper, KeyCode::RightSuper),
     }
 }
 
@@ -142,12 +416,13 @@ pub fn map_key(key: KeyCode) -> Key {
     }
 }
 
-pub fn get_mouse_state() -> MouseInfo {
-    let mouse_position = mouse_position();
+pub fn get_mouse_state(viewport: Viewport, delta: Vec2) -> MouseInfo {
+    let mouse_position = viewport.to_virtual(mouse_position().into());
+    let (wheel_x, wheel_y) = mouse_wheel();
     MouseInfo {
         position: Position {
-            x: mouse_position.0,
-            y: mouse_position.1,
+            x: mouse_position.x,
+            y: mouse_position.y,
         },
         left: ClickInfo {
             pressed: is_mouse_button_pressed(MouseButton::Left),
@@ -164,5 +439,13 @@ pub fn get_mouse_state() -> MouseInfo {
             released: is_mouse_button_released(MouseButton::Middle),
             down: is_mouse_button_down(MouseButton::Middle),
         },
+        delta: Offset {
+            x: delta.x,
+            y: delta.y,
+        },
+        wheel: Offset {
+            x: wheel_x,
+            y: wheel_y,
+        },
     }
 }
@@ -0,0 +1,109 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+#[cfg(not(feature = "hotreload"))]
+pub use game::example::game::host_log::LogLevel;
+
+#[cfg(feature = "hotreload")]
+pub use crate::hotreload::binding::example::game::host_log::LogLevel;
+
+// Older entries are dropped, the full log still goes to stdout
+const MAX_ENTRIES: usize = 1000;
+
+pub const LEVELS: [LogLevel; 5] = [
+    LogLevel::Trace,
+    LogLevel::Debug,
+    LogLevel::Info,
+    LogLevel::Warn,
+    LogLevel::Error,
+];
+
+pub fn severity(level: LogLevel) -> usize {
+    LEVELS.iter().position(|other| *other == level).unwrap_or(0)
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    // Seconds since the log was created
+    pub time: f64,
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+}
+
+// Messages from the guest and the launcher, kept for the console
+#[derive(Clone)]
+pub struct Log {
+    entries: Arc<Mutex<VecDeque<LogEntry>>>,
+    start: Instant,
+    // Put in front of every target, to tell apart several instances writing to one console
+    label: Option<String>,
+}
+
+impl Default for Log {
+    fn default() -> Log {
+        Log {
+            entries: Arc::new(Mutex::new(VecDeque::new())),
+            start: Instant::now(),
+            label: None,
+        }
+    }
+}
+
+impl Log {
+    // Writes to the same entries with every target prefixed by the label, like twin/stdout
+    #[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+    pub fn labelled(&self, label: &str) -> Log {
+        Log {
+            label: Some(label.to_string()),
+            ..self.clone()
+        }
+    }
+
+    pub fn write(&self, level: LogLevel, target: &str, message: &str) {
+        let target = match &self.label {
+            Some(label) => format!("{label}/{target}"),
+            None => target.to_string(),
+        };
+        println!("[{level:?} {target}] {message}");
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == MAX_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(LogEntry {
+            time: self.start.elapsed().as_secs_f64(),
+            level,
+            target,
+            message: message.to_string(),
+        });
+    }
+
+    pub fn error(&self, message: &str) {
+        self.write(LogLevel::Error, "launcher", message);
+    }
+
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labelled_logs_share_entries() {
+        let log = Log::default();
+        let twin = log.labelled("twin");
+        log.write(LogLevel::Info, "stdout", "shown");
+        twin.write(LogLevel::Info, "stdout", "twin");
+
+        let targets: Vec<_> = log.entries().into_iter().map(|e| e.target).collect();
+        assert_eq!(targets, ["stdout", "twin/stdout"]);
+        assert_eq!(twin.entries().len(), 2);
+    }
+}
@@ -2,92 +2,415 @@ use anyhow::Result;
 use async_trait::async_trait;
 use macroquad::prelude::*;
 
+mod actions;
+mod assets;
+mod audio;
+mod clock;
+mod console;
 mod input;
 use input::*;
 
 mod screen;
+mod storage;
 pub use screen::GameScreen;
 
+#[cfg(test)]
+mod temp_dir;
+
+mod font_cache;
+mod gamepad;
+mod log;
 mod texture_cache;
+mod viewport;
+mod window;
 
 #[cfg(feature = "hotreload")]
 mod hotreload;
 
+#[cfg(not(feature = "hotreload"))]
+mod direct;
+
 #[cfg(feature = "hotreload")]
 use crate::hotreload::binding::{
-    example::game::types::{KeyboardInfo, MouseInfo},
-    WebAssemblyContext, WebAssemblyInstance,
+    example::game::types::FrameInput, WebAssemblyContext, WebAssemblyInstance,
 };
 
 #[cfg(not(feature = "hotreload"))]
-pub use game::{
-    exports::example::game::game_api::{KeyboardInfo, MouseInfo},
-    Game,
-};
+pub use game::{exports::example::game::game_api::FrameInput, Game};
 
+use assets::{Assets, ASSETS_DIR};
+use audio::{Audio, MacroquadAudio, NullAudio};
+use clock::VirtualClock;
+use console::Console;
+use font_cache::FontCache;
+use log::Log;
+use storage::Storage;
 use texture_cache::TextureCache;
+use window::Window;
 
 #[async_trait]
 pub trait RunnableGameInstance: Send + Sync {
-    fn run_frame(&self, mouse: MouseInfo, key: KeyboardInfo, screen: GameScreen);
+    fn run_frame(&self, input: FrameInput, screen: GameScreen);
 }
 
 #[cfg(not(feature = "hotreload"))]
 #[async_trait]
 impl RunnableGameInstance for Game {
-    fn run_frame(&self, mouse: MouseInfo, key: KeyboardInfo, screen: GameScreen) {
-        Game::run_frame(self, mouse, key, &screen)
+    fn run_frame(&self, input: FrameInput, screen: GameScreen) {
+        Game::run_frame(self, input, &screen)
     }
 }
 
-async fn run_frame<R: RunnableGameInstance>(instance: &R, screen: GameScreen) {
-    let mouse = get_mouse_state();
-    let key = get_key_info();
-    instance.run_frame(mouse, key, screen.clone());
+// Host services shared by the launcher loop and the host side of the game interfaces
+#[derive(Clone)]
+pub struct HostContext {
+    pub audio: Audio,
+    pub input: InputConfig,
+    pub window: Window,
+    pub log: Log,
+    pub assets: Assets,
+    pub storage: Storage,
+    pub clock: VirtualClock,
+    #[cfg(feature = "hotreload")]
+    pub random: hotreload::wasi::SeededRng,
+}
+
+async fn run_frame<R: RunnableGameInstance>(
+    instance: &R,
+    screen: GameScreen,
+    host: &HostContext,
+    frame: FrameInput,
+    console: &mut Console,
+) {
+    instance.run_frame(frame, screen.clone());
 
-    screen.flush_image_draws().await;
+    host.window.clear();
+    screen.flush_draws().await;
+    console.draw();
+    host.audio.flush().await;
 
     next_frame().await
 }
 
 #[cfg(not(feature = "hotreload"))]
-async fn run(font: Font, texture_cache: TextureCache) -> Result<()> {
+async fn run(font_cache: FontCache, texture_cache: TextureCache, host: HostContext) -> Result<()> {
+    game::set_host(Box::new(direct::DirectHost::new(host.clone())));
     let instance = Game::new();
-    let screen = GameScreen::new(font, texture_cache);
-    loop {
-        run_frame(&instance, screen.clone()).await;
+    let screen = GameScreen::new(font_cache, texture_cache, host.log.clone());
+    let mut input = Input::new(&host);
+    let mut console = Console::new(host.log.clone());
+    while !host.window.should_quit() {
+        let mut frame = input.gather(screen.viewport());
+        console.update(&mut frame);
+        input.freeze_if_paused(&mut frame);
+        run_frame(&instance, screen.clone(), &host, frame, &mut console).await;
     }
+    instance.on_exit();
+    Ok(())
 }
 
 #[cfg(feature = "hotreload")]
-async fn run(font: Font, texture_cache: TextureCache) -> Result<()> {
-    let context = WebAssemblyContext::load()?;
+async fn run(font_cache: FontCache, texture_cache: TextureCache, host: HostContext) -> Result<()> {
+    if let Some(dir) = std::env::args().skip_while(|arg| arg != "--repro").nth(1) {
+        return repro(font_cache, texture_cache, host, dir.into()).await;
+    }
+    if std::env::args().any(|arg| arg == "--check-determinism") {
+        return check_determinism(font_cache, texture_cache, host).await;
+    }
+    host.log.write(
+        log::LogLevel::Info,
+        "launcher",
+        &format!(
+            "Random seed {0}, pass --seed {0} to repeat it",
+            host.random.state()
+        ),
+    );
+
+    let context = WebAssemblyContext::load(host.clone())?;
+    let mut build = crate::hotreload::build_info(0)?;
     let mut assembly = WebAssemblyInstance::load(context)?;
     let mut instance = assembly.create_game_instance()?;
 
     let file_watcher = crate::hotreload::watcher::FileWatcher::new(crate::hotreload::wasm_path()?)?;
-    let screen = GameScreen::new(font, texture_cache);
+    let screen = GameScreen::new(font_cache, texture_cache, host.log.clone());
+    let mut input = Input::new(&host);
+    let mut console = Console::new(host.log.clone());
 
-    loop {
+    while !host.window.should_quit() {
         if file_watcher.changed() {
+            if let Err(e) = instance.on_before_reload() {
+                host.log.error(&format!("Error before reload: {e:?}"));
+            }
             let save_data = instance.save();
-            let context = WebAssemblyContext::load()?;
+            if let Err(e) = &save_data {
+                host.log.error(&format!("Error saving state: {e:?}"));
+            }
+            let context = WebAssemblyContext::load(host.clone())?;
+            let previous = build;
+            build = crate::hotreload::build_info(previous.generation + 1)?;
             assembly = WebAssemblyInstance::load(context)?;
             instance = assembly.create_game_instance()?;
             if let Ok(save_data) = save_data {
-                let _ = instance.load(save_data);
+                if let Err(e) = instance.load(save_data) {
+                    host.log.error(&format!("Error restoring state: {e:?}"));
+                }
+            }
+            if let Err(e) = instance.on_after_reload(previous) {
+                host.log.error(&format!("Error after reload: {e:?}"));
             }
         }
 
-        run_frame(&instance, screen.clone()).await;
+        let mut frame = input.gather(screen.viewport());
+        console.update(&mut frame);
+        input.freeze_if_paused(&mut frame);
+        run_frame(&instance, screen.clone(), &host, frame, &mut console).await;
+    }
+    if let Err(e) = instance.on_exit() {
+        host.log.error(&format!("Error on exit: {e:?}"));
+    }
+    Ok(())
+}
+
+// Replays a crash bundle from its save with the recorded frame inputs, then carries on with live input
+#[cfg(feature = "hotreload")]
+async fn repro(
+    font_cache: FontCache,
+    texture_cache: TextureCache,
+    host: HostContext,
+    dir: std::path::PathBuf,
+) -> Result<()> {
+    let bundle = crate::hotreload::crash::CrashBundle::read(&dir)?;
+    let mut context = WebAssemblyContext::load(host.clone())?;
+    context.disable_crash_bundles();
+    let mut assembly = WebAssemblyInstance::from_component(context, bundle.component)?;
+    let instance = assembly.create_game_instance()?;
+    if let Some(save_data) = bundle.save {
+        if let Err(e) = instance.load(save_data) {
+            host.log.error(&format!("Error restoring state: {e:?}"));
+        }
+    }
+    if let Some(random) = bundle.random {
+        host.random.set_state(random);
+    }
+
+    let screen = GameScreen::new(font_cache, texture_cache, host.log.clone());
+    let mut input = Input::new(&host);
+    let mut console = Console::new(host.log.clone());
+    let mut frames = bundle.inputs.into_iter();
+    host.log.write(
+        log::LogLevel::Info,
+        "launcher",
+        &format!("Replaying {} frames from {}", frames.len(), dir.display()),
+    );
+
+    while !host.window.should_quit() {
+        // Live input is still gathered during the replay, so it doesn't pile up for afterwards
+        let mut live = input.gather(screen.viewport());
+        console.update(&mut live);
+        input.freeze_if_paused(&mut live);
+
+        let frame = if frames.len() == 0 {
+            live
+        } else if input.frozen() {
+            // Pausing holds the replay, as the game may only see recorded frames until it ends
+            host.window.clear();
+            console.draw();
+            next_frame().await;
+            continue;
+        } else {
+            let frame = frames.next().unwrap();
+            if frames.len() == 0 {
+                host.log
+                    .write(log::LogLevel::Info, "launcher", "Replay finished");
+            }
+            frame
+        };
+        run_frame(&instance, screen.clone(), &host, frame, &mut console).await;
+    }
+    if let Err(e) = instance.on_exit() {
+        host.log.error(&format!("Error on exit: {e:?}"));
+    }
+    Ok(())
+}
+
+// Runs a second instance of the game next to the shown one with the same input, and stops at the
+// first frame where their saves differ with a field by field diff of the two states
+#[cfg(feature = "hotreload")]
+async fn check_determinism(
+    font_cache: FontCache,
+    texture_cache: TextureCache,
+    host: HostContext,
+) -> Result<()> {
+    use crate::audio::MutedAudio;
+    use crate::hotreload::{
+        determinism::{diff_bytes, diff_states, state_hash},
+        wasi::SeededRng,
+    };
+
+    // The twin writes its slots to a copy, so both start from the same saves without the twin
+    // changing the real ones
+    let twin_data = std::env::temp_dir().join(format!("twin-storage-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&twin_data);
+    let twin_storage = host
+        .storage
+        .copy_to(&twin_data)
+        .map_err(anyhow::Error::msg)?;
+
+    // The twin gets its own generator from the same seed, drawing from a shared one would
+    // hand each instance different numbers
+    let twin_log = host.log.labelled("twin");
+    let twin_host = HostContext {
+        audio: Audio::new(MutedAudio),
+        window: Window::headless(twin_log.clone()),
+        log: twin_log,
+        storage: twin_storage,
+        random: SeededRng::new(host.random.state()),
+        ..host.clone()
+    };
+    host.log.write(
+        log::LogLevel::Info,
+        "launcher",
+        &format!(
+            "Checking determinism with seed {0}, pass --seed {0} to repeat it",
+            host.random.state()
+        ),
+    );
+
+    // Both load the same bytes, so a rebuild between the two loads can't tell them apart
+    let component = std::fs::read(crate::hotreload::wasm_path()?)?;
+    let context = WebAssemblyContext::load(host.clone())?;
+    let mut assembly = WebAssemblyInstance::from_component(context, component.clone())?;
+    let instance = assembly.create_game_instance()?;
+    let mut twin_context = WebAssemblyContext::load(twin_host.clone())?;
+    // The twin records too, as taking a snapshot calls save() on the instance
+    twin_context.discard_crash_bundles();
+    let mut twin_assembly = WebAssemblyInstance::from_component(twin_context, component)?;
+    let twin = twin_assembly.create_game_instance()?;
+
+    let screen = GameScreen::new(font_cache.clone(), texture_cache.clone(), host.log.clone());
+    let twin_screen = GameScreen::new(font_cache, texture_cache, twin_host.log.clone());
+    let mut input = Input::new(&host);
+    let mut console = Console::new(host.log.clone());
+    let mut checking = true;
+    let mut frame_number: u64 = 0;
+
+    while !host.window.should_quit() {
+        let mut frame = input.gather(screen.viewport());
+        console.update(&mut frame);
+        input.freeze_if_paused(&mut frame);
+
+        if checking {
+            if let Err(e) = twin.run_recorded_frame(frame.clone(), twin_screen.clone()) {
+                host.log
+                    .error(&format!("Twin instance failed, checking stopped: {e:?}"));
+                checking = false;
+            }
+            twin_screen.discard_draws();
+            twin_host.audio.flush().await;
+        }
+        run_frame(&instance, screen.clone(), &host, frame, &mut console).await;
+        if !checking {
+            continue;
+        }
+
+        frame_number += 1;
+        let saves = instance.save().and_then(|save| Ok((save, twin.save()?)));
+        let (save, twin_save) = match saves {
+            Ok(saves) => saves,
+            Err(e) => {
+                host.log.error(&format!(
+                    "Unable to save instances, checking stopped: {e:?}"
+                ));
+                checking = false;
+                continue;
+            }
+        };
+        if state_hash(&save) != state_hash(&twin_save) {
+            // The twin stops running there, and the shown instance is paused at the frame
+            checking = false;
+            host.clock.pause();
+            let states = instance
+                .debug_state()
+                .and_then(|state| Ok((state, twin.debug_state()?)));
+            // Without game-debug, or when the difference isn't in the dump, the saves are compared
+            let save_bytes = || diff_bytes(&save, &twin_save).join("\n");
+            let details = match states {
+                Ok((Some(state), Some(twin_state))) => {
+                    let differences = diff_states(&state, &twin_state);
+                    if differences.is_empty() {
+                        format!("The debug states match, save bytes:\n{}", save_bytes())
+                    } else {
+                        differences.join("\n")
+                    }
+                }
+                Ok(_) => format!("No game-debug export, save bytes:\n{}", save_bytes()),
+                Err(e) => format!(
+                    "Unable to get debug states: {e:?}\nSave bytes:\n{}",
+                    save_bytes()
+                ),
+            };
+            host.log.error(&format!(
+                "Instances diverged on frame {frame_number}, shown != twin:\n{details}"
+            ));
+        }
+    }
+    if let Err(e) = instance.on_exit() {
+        host.log.error(&format!("Error on exit: {e:?}"));
+    }
+    let _ = std::fs::remove_dir_all(&twin_data);
+    Ok(())
+}
+
+// Random unless given with --seed, the seed is logged so a session can be repeated
+// A seed that doesn't parse stops the launcher, as a random one would silently play a different session
+#[cfg(feature = "hotreload")]
+fn seed() -> Result<u64> {
+    use anyhow::Context;
+
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed");
+    if args.next().is_none() {
+        return Ok(std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64));
     }
+    let seed = args.next().context("--seed needs a number")?;
+    seed.parse()
+        .with_context(|| format!("Invalid --seed {seed}, expected a whole number"))
 }
 
 #[macroquad::main("Rust Hotreload Example")]
 async fn main() -> Result<()> {
     let font = load_ttf_font_from_bytes(include_bytes!("../../resources/Kreon-Regular.ttf"))
         .expect("Unable to load font");
+    let font_cache = FontCache::new(font);
     let texture_cache = TextureCache::default();
+    let log = Log::default();
+    let audio = if std::env::args().any(|arg| arg == "--no-audio") {
+        Audio::new(NullAudio::new(log.clone()))
+    } else {
+        Audio::new(MacroquadAudio::default())
+    };
+    let host = HostContext {
+        audio,
+        input: InputConfig::default(),
+        window: Window::new(log.clone()),
+        log,
+        assets: Assets::new(
+            std::env::args()
+                .skip_while(|arg| arg != "--assets")
+                .nth(1)
+                .unwrap_or(ASSETS_DIR.to_string()),
+        ),
+        storage: Storage::new(
+            std::env::args()
+                .skip_while(|arg| arg != "--data")
+                .nth(1)
+                .map_or_else(storage::default_data_dir, Into::into),
+        ),
+        clock: VirtualClock::default(),
+        #[cfg(feature = "hotreload")]
+        random: hotreload::wasi::SeededRng::new(seed()?),
+    };
 
-    run(font, texture_cache).await
+    run(font_cache, texture_cache, host).await
 }
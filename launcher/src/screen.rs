@@ -1,40 +1,363 @@
 use std::sync::Arc;
 
 use macroquad::{
-    color::{Color, WHITE},
-    math::Vec2,
+    camera::{set_camera, set_default_camera, Camera, Camera2D},
+    color::Color,
+    input::{set_cursor_grab, show_mouse},
+    math::{vec2, vec3, Mat4, Quat, Rect, Vec2},
+    prelude::clear_background,
+    prelude::RenderPass,
     shapes::draw_line,
-    text::{draw_text_ex, Font, TextParams},
-    texture::{draw_texture_ex, DrawTextureParams, Texture2D},
+    text::{draw_text_ex, measure_text, TextParams},
+    texture::{
+        draw_texture_ex, DrawTextureParams, FilterMode as TextureFilter, RenderTarget, Texture2D,
+    },
+    window::{get_internal_gl, screen_dpi_scale, screen_height, screen_width},
 };
 
-use crate::texture_cache::TextureCache;
+use crate::{
+    font_cache::FontCache,
+    log::Log,
+    texture_cache::{create_texture, crop_pixels, update_texture, TextureCache},
+    viewport::Viewport,
+};
+
+#[cfg(not(feature = "hotreload"))]
+use game::{
+    example::game::types::{
+        FilterMode, GameCamera, GameColor, ImageParams, Position, Rect as GameRect, Size,
+        Transform, VirtualResolution,
+    },
+    Font, RenderTarget as GameRenderTarget, Texture as GameTexture,
+};
 
 #[cfg(not(feature = "hotreload"))]
-use game::example::game::types::{GameColor, Position, Size};
+use crate::direct::downcast_handle;
 
 #[cfg(feature = "hotreload")]
-use crate::hotreload::binding::example::game::types::{GameColor, Position, Size};
+use crate::hotreload::binding::example::game::types::{
+    FilterMode, GameCamera, GameColor, ImageParams, Position, Rect as GameRect, Size, Transform,
+    VirtualResolution,
+};
+
+enum DrawCommand {
+    Text {
+        text: String,
+        position: Position,
+        size: f32,
+        color: GameColor,
+        font: macroquad::text::Font,
+    },
+    Line {
+        first: Position,
+        second: Position,
+        thickness: f32,
+        color: GameColor,
+    },
+    Image {
+        filename: String,
+        position: Position,
+        params: ImageParams,
+    },
+    Texture {
+        texture: Texture2D,
+        position: Position,
+        params: ImageParams,
+    },
+    Clear {
+        color: GameColor,
+    },
+}
+
+// Draws are queued in call order and flushed after the frame to prevent needing async in render
+struct QueuedDraw {
+    command: DrawCommand,
+    // Resolved when queued, as the guest may change the camera, transforms or clip before the flush
+    matrix: Mat4,
+    clip: Option<Rect>,
+    target: Option<RenderTarget>,
+}
+
+// What a draw state draws into, generic so the state stack can be tested without a GL context
+trait DrawTarget: Clone {
+    fn size(&self) -> Vec2;
+}
+
+impl DrawTarget for RenderTarget {
+    fn size(&self) -> Vec2 {
+        self.texture.size()
+    }
+}
+
+// Each render target pushed gets a fresh state, which is restored when it is popped
+struct DrawState<T = RenderTarget> {
+    target: Option<T>,
+    camera: Option<GameCamera>,
+    transforms: Vec<Mat4>,
+    clips: Vec<Rect>,
+}
+
+impl<T> Default for DrawState<T> {
+    fn default() -> Self {
+        DrawState {
+            target: None,
+            camera: None,
+            transforms: vec![],
+            clips: vec![],
+        }
+    }
+}
+
+impl<T: DrawTarget> DrawState<T> {
+    fn viewport(&self, screen: Viewport) -> Viewport {
+        match &self.target {
+            Some(target) => Viewport::identity(target.size()),
+            None => screen,
+        }
+    }
 
-struct ImageRenderRequest {
-    filename: String,
-    position: Position,
-    size: Option<Size>,
+    fn camera(&self, screen: Viewport) -> Camera2D {
+        let viewport = self.viewport(screen);
+        let (target, zoom, rotation) = match &self.camera {
+            Some(camera) => (
+                vec2(camera.target.x, camera.target.y),
+                camera.zoom,
+                camera.rotation,
+            ),
+            None => (viewport.size / 2.0, 1.0, 0.0),
+        };
+        // The letterboxed area is always centered, so the target maps onto the center of the surface
+        let zoom = 2.0 * viewport.scale * zoom / viewport.surface;
+        // Without a render target Camera2D flips y, so the flip is undone here for render targets
+        Camera2D {
+            target,
+            zoom: vec2(zoom.x, -self.y_axis() * zoom.y),
+            rotation: rotation.to_degrees(),
+            ..Default::default()
+        }
+    }
+
+    fn transform(&self) -> Mat4 {
+        self.transforms.last().copied().unwrap_or(Mat4::IDENTITY)
+    }
+
+    fn matrix(&self, screen: Viewport) -> Mat4 {
+        self.camera(screen).matrix() * self.transform()
+    }
+
+    fn clip(&self, screen: Viewport) -> Option<Rect> {
+        self.clips
+            .last()
+            .copied()
+            .or_else(|| self.viewport(screen).bounds())
+    }
+
+    fn push_transform(&mut self, transform: Transform) {
+        let local = Mat4::from_scale_rotation_translation(
+            vec3(transform.scale_x, transform.scale_y, 1.0),
+            Quat::from_rotation_z(transform.rotation),
+            vec3(transform.translation.x, transform.translation.y, 0.0),
+        );
+        self.transforms.push(self.transform() * local);
+    }
+
+    fn push_clip(&mut self, screen: Viewport, rect: GameRect) {
+        let rect = self.viewport(screen).to_surface_rect(Rect::new(
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+        ));
+        let clip = match self.clip(screen) {
+            Some(parent) => parent
+                .intersect(rect)
+                .unwrap_or(Rect::new(rect.x, rect.y, 0.0, 0.0)),
+            None => rect,
+        };
+        self.clips.push(clip);
+    }
+
+    // Screen pixels grow downwards while clip space points up, render targets are drawn without that flip
+    fn y_axis(&self) -> f32 {
+        if self.target.is_some() {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    fn world_to_screen(&self, screen: Viewport, position: Vec2) -> Vec2 {
+        let viewport = self.viewport(screen);
+        let clip = self
+            .matrix(screen)
+            .transform_point3(vec3(position.x, position.y, 0.0));
+        let surface = vec2(clip.x + 1.0, self.y_axis() * clip.y + 1.0) / 2.0 * viewport.surface;
+        viewport.to_virtual(surface)
+    }
+
+    fn screen_to_world(&self, screen: Viewport, position: Vec2) -> Vec2 {
+        let viewport = self.viewport(screen);
+        let surface = viewport.to_surface(position) / viewport.surface * 2.0 - 1.0;
+        let clip = vec3(surface.x, self.y_axis() * surface.y, 0.0);
+        let world = self.matrix(screen).inverse().transform_point3(clip);
+        vec2(world.x, world.y)
+    }
+}
+
+// The window's draw state with the states of the render targets pushed over it
+struct DrawStack<T = RenderTarget> {
+    states: Vec<DrawState<T>>,
+}
+
+impl<T> Default for DrawStack<T> {
+    fn default() -> Self {
+        DrawStack {
+            states: vec![DrawState::default()],
+        }
+    }
+}
+
+impl<T> DrawStack<T> {
+    fn current(&mut self) -> &mut DrawState<T> {
+        self.states
+            .last_mut()
+            .expect("Root draw state is never popped")
+    }
+
+    fn push_target(&mut self, target: T) {
+        self.states.push(DrawState {
+            target: Some(target),
+            ..Default::default()
+        });
+    }
+
+    // Popping more targets than were pushed leaves the window's state in place
+    fn pop_target(&mut self) {
+        if self.states.len() > 1 {
+            self.states.pop();
+        }
+    }
+}
+
+fn apply_clip(clip: Option<Rect>, target: Option<&RenderTarget>) {
+    let scissor = clip.map(|clip| match target {
+        // Render targets are drawn without the y flip, so flip the rect to match the scissor origin
+        Some(target) => (
+            clip.x as i32,
+            (target.texture.height() - clip.y - clip.h) as i32,
+            clip.w as i32,
+            clip.h as i32,
+        ),
+        // The scissor rect is in framebuffer pixels, while guest coordinates are logical window pixels
+        None => {
+            let scale = screen_dpi_scale();
+            (
+                (clip.x * scale) as i32,
+                (clip.y * scale) as i32,
+                (clip.w * scale) as i32,
+                (clip.h * scale) as i32,
+            )
+        }
+    });
+    unsafe {
+        get_internal_gl().quad_gl.scissor(scissor);
+    }
+}
+
+struct QueuedCamera {
+    matrix: Mat4,
+    target: Option<RenderTarget>,
+}
+
+impl Camera for QueuedCamera {
+    fn matrix(&self) -> Mat4 {
+        self.matrix
+    }
+
+    fn depth_enabled(&self) -> bool {
+        false
+    }
+
+    fn render_pass(&self) -> Option<RenderPass> {
+        self.target
+            .as_ref()
+            .map(|target| target.render_pass.clone())
+    }
+
+    fn viewport(&self) -> Option<(i32, i32, i32, i32)> {
+        None
+    }
+}
+
+pub fn convert_color(color: GameColor) -> Color {
+    Color {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+        a: color.a,
+    }
+}
+
+fn default_image_params() -> ImageParams {
+    ImageParams {
+        size: None,
+        source: None,
+        rotation: 0.0,
+        pivot: None,
+        flip_x: false,
+        flip_y: false,
+        tint: GameColor {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        },
+    }
+}
+
+fn convert_image_params(params: ImageParams) -> DrawTextureParams {
+    DrawTextureParams {
+        dest_size: params.size.map(|size| Vec2 {
+            x: size.width,
+            y: size.height,
+        }),
+        source: params
+            .source
+            .map(|source| Rect::new(source.x, source.y, source.width, source.height)),
+        rotation: params.rotation,
+        flip_x: params.flip_x,
+        flip_y: params.flip_y,
+        pivot: params.pivot.map(|pivot| Vec2 {
+            x: pivot.x,
+            y: pivot.y,
+        }),
+    }
 }
 
 #[derive(Clone)]
 pub struct GameScreen {
-    font: Font,
+    font_cache: Arc<std::sync::Mutex<FontCache>>,
     texture_cache: Arc<async_mutex::Mutex<TextureCache>>,
-    image_requests: Arc<std::sync::Mutex<Vec<ImageRenderRequest>>>,
+    draws: Arc<std::sync::Mutex<Vec<QueuedDraw>>>,
+    states: Arc<std::sync::Mutex<DrawStack>>,
+    // Kept between frames so presenting a framebuffer of the same size only re-uploads pixels
+    framebuffer: Arc<std::sync::Mutex<Option<Texture2D>>>,
+    resolution: Arc<std::sync::Mutex<Option<VirtualResolution>>>,
+    // Only direct mode has handles to report on, the component model checks resource types itself
+    #[cfg_attr(feature = "hotreload", allow(dead_code))]
+    log: Log,
 }
 
 impl GameScreen {
-    pub fn new(font: Font, texture_cache: TextureCache) -> Self {
+    pub fn new(font_cache: FontCache, texture_cache: TextureCache, log: Log) -> Self {
         Self {
-            font,
+            font_cache: Arc::new(std::sync::Mutex::new(font_cache)),
             texture_cache: Arc::new(async_mutex::Mutex::new(texture_cache)),
-            image_requests: Arc::new(std::sync::Mutex::new(vec![])),
+            draws: Arc::new(std::sync::Mutex::new(vec![])),
+            states: Arc::new(std::sync::Mutex::new(DrawStack::default())),
+            framebuffer: Arc::new(std::sync::Mutex::new(None)),
+            resolution: Arc::new(std::sync::Mutex::new(None)),
+            log,
         }
     }
 
@@ -43,86 +366,559 @@ impl GameScreen {
         texture_cache.get(filename).await.ok()
     }
 
-    pub fn draw_text(&self, text: &str, position: Position, size: f32, color: GameColor) {
-        draw_text_ex(
-            text,
-            position.x,
-            position.y,
-            TextParams {
-                font: Some(&self.font),
-                font_size: size as u16,
-                color: Color {
-                    r: color.r,
-                    g: color.g,
-                    b: color.b,
-                    a: color.a,
-                },
-                ..Default::default()
-            },
-        );
+    pub fn viewport(&self) -> Viewport {
+        Viewport::new(*self.resolution.lock().unwrap())
+    }
+
+    fn with_state<R>(&self, f: impl FnOnce(&mut DrawState, Viewport) -> R) -> R {
+        let screen = self.viewport();
+        f(self.states.lock().unwrap().current(), screen)
+    }
+
+    fn queue(&self, command: DrawCommand) {
+        let (matrix, clip, target) = self.with_state(|state, screen| {
+            (
+                state.matrix(screen),
+                state.clip(screen),
+                state.target.clone(),
+            )
+        });
+        self.draws.lock().unwrap().push(QueuedDraw {
+            command,
+            matrix,
+            clip,
+            target,
+        });
+    }
+
+    pub fn draw_text(
+        &self,
+        text: &str,
+        position: Position,
+        size: f32,
+        color: GameColor,
+        font: Option<&str>,
+    ) {
+        let font = self.font_cache.lock().unwrap().get(font);
+        self.queue(DrawCommand::Text {
+            text: text.to_string(),
+            position,
+            size,
+            color,
+            font,
+        });
+    }
+
+    pub fn measure_text(&self, text: &str, size: f32, font: Option<&str>) -> Size {
+        let font = self.font_cache.lock().unwrap().get(font);
+        let dimensions = measure_text(text, Some(&font), size as u16, 1.0);
+        Size {
+            width: dimensions.width,
+            height: dimensions.height,
+        }
     }
 
-    // Make this store a list and flush after frame to prevent needing async in render
     pub fn draw_image(&self, filename: &str, position: Position, size: Option<Size>) {
-        self.image_requests
-            .lock()
-            .unwrap()
-            .push(ImageRenderRequest {
-                filename: filename.to_string(),
-                position,
+        self.draw_image_ex(
+            filename,
+            position,
+            ImageParams {
                 size,
-            });
+                ..default_image_params()
+            },
+        );
     }
 
-    pub async fn flush_image_draws(&self) {
-        let images: Vec<ImageRenderRequest> = {
-            let mut image_requests = self.image_requests.lock().unwrap();
-            image_requests.drain(..).collect()
-        };
-        for image in images {
-            // Ignore image loading errors and just skip render
-            if let Some(texture) = self.fetch_texture(&image.filename).await {
-                let mut params = DrawTextureParams::default();
-                if let Some(size) = image.size {
-                    params.dest_size = Some(Vec2 {
-                        x: size.width,
-                        y: size.height,
-                    })
-                }
-                draw_texture_ex(&texture, image.position.x, image.position.y, WHITE, params);
-            }
-        }
+    pub fn draw_image_ex(&self, filename: &str, position: Position, params: ImageParams) {
+        self.queue(DrawCommand::Image {
+            filename: filename.to_string(),
+            position,
+            params,
+        });
     }
 
     pub fn draw_line(&self, first: Position, second: Position, thickness: f32, color: GameColor) {
-        draw_line(
-            first.x,
-            first.y,
-            second.x,
-            second.y,
+        self.queue(DrawCommand::Line {
+            first,
+            second,
             thickness,
-            Color {
-                r: color.r,
-                g: color.g,
-                b: color.b,
-                a: color.a,
+            color,
+        });
+    }
+
+    pub fn draw_render_target(
+        &self,
+        target: &RenderTarget,
+        position: Position,
+        params: ImageParams,
+    ) {
+        self.queue(DrawCommand::Texture {
+            texture: target.texture.clone(),
+            position,
+            params,
+        });
+    }
+
+    pub fn draw_texture(&self, texture: &Texture2D, position: Position, params: ImageParams) {
+        self.queue(DrawCommand::Texture {
+            texture: texture.clone(),
+            position,
+            params,
+        });
+    }
+
+    pub fn present_framebuffer(&self, width: u32, height: u32, pixels: &[u8], filter: FilterMode) {
+        // An empty frame has nothing to show and would scale by infinity
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let (width, height, pixels) = crop_pixels(width, height, pixels);
+        let texture = {
+            let mut framebuffer = self.framebuffer.lock().unwrap();
+            let texture = match framebuffer.take() {
+                Some(texture)
+                    if texture.width() as u32 == width && texture.height() as u32 == height =>
+                {
+                    update_texture(&texture, &pixels);
+                    texture
+                }
+                _ => create_texture(width, height, &pixels),
+            };
+            texture.set_filter(match filter {
+                FilterMode::Nearest => TextureFilter::Nearest,
+                FilterMode::Linear => TextureFilter::Linear,
+            });
+            *framebuffer = Some(texture.clone());
+            texture
+        };
+
+        let window = vec2(screen_width(), screen_height());
+        let frame = texture.size();
+        let scale = (window.x / frame.x).min(window.y / frame.y);
+        let size = frame * scale;
+        let offset = (window - size) / 2.0;
+
+        let screen = DrawState::<RenderTarget>::default();
+        self.draws.lock().unwrap().push(QueuedDraw {
+            command: DrawCommand::Texture {
+                texture,
+                position: Position {
+                    x: offset.x,
+                    y: offset.y,
+                },
+                params: ImageParams {
+                    size: Some(Size {
+                        width: size.x,
+                        height: size.y,
+                    }),
+                    ..default_image_params()
+                },
             },
-        )
+            matrix: screen.matrix(Viewport::identity(window)),
+            clip: None,
+            target: None,
+        });
+    }
+
+    pub fn set_virtual_resolution(&self, resolution: Option<VirtualResolution>) {
+        *self.resolution.lock().unwrap() = resolution;
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool) {
+        show_mouse(visible);
+    }
+
+    pub fn set_cursor_grab(&self, grab: bool) {
+        set_cursor_grab(grab);
+    }
+
+    pub fn clear(&self, color: GameColor) {
+        self.queue(DrawCommand::Clear { color });
+    }
+
+    pub fn push_transform(&self, transform: Transform) {
+        self.with_state(|state, _| state.push_transform(transform));
+    }
+
+    pub fn pop_transform(&self) {
+        self.with_state(|state, _| state.transforms.pop());
+    }
+
+    pub fn set_camera(&self, camera: Option<GameCamera>) {
+        self.with_state(|state, _| state.camera = camera);
+    }
+
+    pub fn world_to_screen(&self, position: Position) -> Position {
+        let screen = self.with_state(|state, screen| {
+            state.world_to_screen(screen, vec2(position.x, position.y))
+        });
+        Position {
+            x: screen.x,
+            y: screen.y,
+        }
+    }
+
+    pub fn screen_to_world(&self, position: Position) -> Position {
+        let world = self.with_state(|state, screen| {
+            state.screen_to_world(screen, vec2(position.x, position.y))
+        });
+        Position {
+            x: world.x,
+            y: world.y,
+        }
+    }
+
+    pub fn push_clip(&self, rect: GameRect) {
+        self.with_state(|state, screen| state.push_clip(screen, rect));
+    }
+
+    pub fn pop_clip(&self) {
+        self.with_state(|state, _| state.clips.pop());
+    }
+
+    pub fn push_render_target(&self, target: &RenderTarget) {
+        self.states.lock().unwrap().push_target(target.clone());
+    }
+
+    pub fn pop_render_target(&self) {
+        self.states.lock().unwrap().pop_target();
+    }
+
+    // Drops the frame's draws instead of drawing them, for instances that run without being shown
+    #[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+    pub fn discard_draws(&self) {
+        self.draws.lock().unwrap().clear();
+        *self.states.lock().unwrap() = DrawStack::default();
+    }
+
+    pub async fn flush_draws(&self) {
+        let draws: Vec<QueuedDraw> = {
+            let mut draws = self.draws.lock().unwrap();
+            draws.drain(..).collect()
+        };
+        *self.states.lock().unwrap() = DrawStack::default();
+
+        let mut current_matrix = None;
+        let mut current_clip = None;
+        let mut current_target = None;
+        for draw in draws {
+            let target = draw.target.as_ref().map(|target| target.texture.clone());
+            if current_target != target {
+                // Setting a camera without a render pass keeps drawing into the previous target
+                set_default_camera();
+                current_target = target;
+                current_matrix = None;
+                current_clip = None;
+            }
+            if current_matrix != Some(draw.matrix) {
+                set_camera(&QueuedCamera {
+                    matrix: draw.matrix,
+                    target: draw.target.clone(),
+                });
+                current_matrix = Some(draw.matrix);
+            }
+            if current_clip != draw.clip {
+                apply_clip(draw.clip, draw.target.as_ref());
+                current_clip = draw.clip;
+            }
+
+            match draw.command {
+                DrawCommand::Text {
+                    text,
+                    position,
+                    size,
+                    color,
+                    font,
+                } => {
+                    draw_text_ex(
+                        &text,
+                        position.x,
+                        position.y,
+                        TextParams {
+                            font: Some(&font),
+                            font_size: size as u16,
+                            color: convert_color(color),
+                            ..Default::default()
+                        },
+                    );
+                }
+                DrawCommand::Line {
+                    first,
+                    second,
+                    thickness,
+                    color,
+                } => draw_line(
+                    first.x,
+                    first.y,
+                    second.x,
+                    second.y,
+                    thickness,
+                    convert_color(color),
+                ),
+                DrawCommand::Image {
+                    filename,
+                    position,
+                    params,
+                } => {
+                    // Ignore image loading errors and just skip render
+                    if let Some(texture) = self.fetch_texture(&filename).await {
+                        let tint = convert_color(params.tint);
+                        let params = convert_image_params(params);
+                        draw_texture_ex(&texture, position.x, position.y, tint, params);
+                    }
+                }
+                DrawCommand::Texture {
+                    texture,
+                    position,
+                    params,
+                } => {
+                    let tint = convert_color(params.tint);
+                    let params = convert_image_params(params);
+                    draw_texture_ex(&texture, position.x, position.y, tint, params);
+                }
+                DrawCommand::Clear { color } => clear_background(convert_color(color)),
+            }
+        }
+        apply_clip(None, None);
+        set_default_camera();
     }
 }
 
 #[cfg(not(feature = "hotreload"))]
 #[async_trait::async_trait]
 impl game::GameScreenInterface for GameScreen {
-    fn draw_text(&self, text: &str, position: Position, size: f32, color: GameColor) {
-        self.draw_text(text, position, size, color);
+    fn draw_text(
+        &self,
+        text: &str,
+        position: Position,
+        size: f32,
+        color: GameColor,
+        font: Option<&Font>,
+    ) {
+        self.draw_text(text, position, size, color, font.map(Font::path));
+    }
+
+    fn measure_text(&self, text: &str, size: f32, font: Option<&Font>) -> Size {
+        self.measure_text(text, size, font.map(Font::path))
     }
 
     fn draw_image(&self, filename: &str, position: Position, size: Option<Size>) {
         self.draw_image(filename, position, size);
     }
 
+    fn draw_image_ex(&self, filename: &str, position: Position, params: ImageParams) {
+        self.draw_image_ex(filename, position, params);
+    }
+
     fn draw_line(&self, first: Position, second: Position, thickness: f32, color: GameColor) {
         self.draw_line(first, second, thickness, color);
     }
+
+    fn push_transform(&self, transform: Transform) {
+        self.push_transform(transform);
+    }
+
+    fn pop_transform(&self) {
+        self.pop_transform();
+    }
+
+    fn set_camera(&self, camera: Option<GameCamera>) {
+        self.set_camera(camera);
+    }
+
+    fn world_to_screen(&self, position: Position) -> Position {
+        self.world_to_screen(position)
+    }
+
+    fn screen_to_world(&self, position: Position) -> Position {
+        self.screen_to_world(position)
+    }
+
+    fn push_clip(&self, rect: GameRect) {
+        self.push_clip(rect);
+    }
+
+    fn pop_clip(&self) {
+        self.pop_clip();
+    }
+
+    fn draw_render_target(
+        &self,
+        target: &GameRenderTarget,
+        position: Position,
+        params: ImageParams,
+    ) {
+        if let Some(target) = downcast_handle(&self.log, target.handle(), "render target") {
+            self.draw_render_target(target, position, params);
+        }
+    }
+
+    fn draw_texture(&self, texture: &GameTexture, position: Position, params: ImageParams) {
+        if let Some(texture) = downcast_handle(&self.log, texture.handle(), "texture") {
+            self.draw_texture(texture, position, params);
+        }
+    }
+
+    fn clear(&self, color: GameColor) {
+        self.clear(color);
+    }
+
+    fn set_virtual_resolution(&self, resolution: Option<VirtualResolution>) {
+        self.set_virtual_resolution(resolution);
+    }
+
+    fn set_cursor_visible(&self, visible: bool) {
+        self.set_cursor_visible(visible);
+    }
+
+    fn set_cursor_grab(&self, grab: bool) {
+        self.set_cursor_grab(grab);
+    }
+
+    fn present_framebuffer(&self, width: u32, height: u32, pixels: &[u8], filter: FilterMode) {
+        self.present_framebuffer(width, height, pixels, filter);
+    }
+
+    fn push_render_target(&self, target: &GameRenderTarget) {
+        if let Some(target) = downcast_handle(&self.log, target.handle(), "render target") {
+            self.push_render_target(target);
+        }
+    }
+
+    fn pop_render_target(&self) {
+        self.pop_render_target();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands in for a render target of the given size, as creating a real one needs a GL context
+    #[derive(Clone, Debug, PartialEq)]
+    struct FakeTarget(Vec2);
+
+    impl DrawTarget for FakeTarget {
+        fn size(&self) -> Vec2 {
+            self.0
+        }
+    }
+
+    fn window() -> Viewport {
+        Viewport::fit(vec2(800.0, 600.0), None)
+    }
+
+    fn position(x: f32, y: f32) -> Position {
+        Position { x, y }
+    }
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            (actual - expected).length() < 1e-3,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn render_targets_nest_and_pop_in_order() {
+        let mut stack = DrawStack::default();
+        stack.push_target(FakeTarget(vec2(64.0, 64.0)));
+        stack.push_target(FakeTarget(vec2(32.0, 16.0)));
+        assert_eq!(stack.current().target, Some(FakeTarget(vec2(32.0, 16.0))));
+
+        stack.pop_target();
+        assert_eq!(stack.current().target, Some(FakeTarget(vec2(64.0, 64.0))));
+        stack.pop_target();
+        assert_eq!(stack.current().target, None);
+
+        // Extra pops keep the window's state
+        stack.pop_target();
+        assert_eq!(stack.states.len(), 1);
+    }
+
+    #[test]
+    fn popping_a_render_target_restores_the_camera_transforms_and_clips() {
+        let mut stack = DrawStack::<FakeTarget>::default();
+        let window = window();
+        let root = stack.current();
+        root.camera = Some(GameCamera {
+            target: position(100.0, 50.0),
+            zoom: 2.0,
+            rotation: 0.5,
+        });
+        root.push_transform(Transform {
+            translation: position(10.0, 20.0),
+            rotation: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+        });
+        root.push_clip(
+            window,
+            GameRect {
+                x: 10.0,
+                y: 10.0,
+                width: 200.0,
+                height: 100.0,
+            },
+        );
+        let (matrix, clip) = (root.matrix(window), root.clip(window));
+
+        stack.push_target(FakeTarget(vec2(64.0, 32.0)));
+        let target = stack.current();
+        // A pushed target starts without the window's camera, transforms or clips
+        assert_eq!(target.clip(window), None);
+        assert_near(
+            target.world_to_screen(window, vec2(5.0, 6.0)),
+            vec2(5.0, 6.0),
+        );
+        target.camera = Some(GameCamera {
+            target: position(0.0, 0.0),
+            zoom: 4.0,
+            rotation: 0.0,
+        });
+        target.push_clip(
+            window,
+            GameRect {
+                x: 0.0,
+                y: 0.0,
+                width: 8.0,
+                height: 8.0,
+            },
+        );
+        stack.pop_target();
+
+        let root = stack.current();
+        assert_eq!(root.matrix(window), matrix);
+        assert_eq!(root.clip(window), clip);
+    }
+
+    #[test]
+    fn render_targets_map_positions_without_the_window_flip() {
+        let mut stack = DrawStack::default();
+        let window = window();
+        stack.push_target(FakeTarget(vec2(100.0, 50.0)));
+        let target = stack.current();
+        target.camera = Some(GameCamera {
+            target: position(50.0, 25.0),
+            zoom: 2.0,
+            rotation: 0.0,
+        });
+        // Zoomed in around the center, a point above it stays above it
+        assert_near(
+            target.world_to_screen(window, vec2(50.0, 20.0)),
+            vec2(50.0, 15.0),
+        );
+        for world in [vec2(0.0, 0.0), vec2(12.5, 40.0)] {
+            let screen = target.world_to_screen(window, world);
+            assert_near(target.screen_to_world(window, screen), world);
+        }
+
+        stack.pop_target();
+        let root = stack.current();
+        assert_near(
+            root.world_to_screen(window, vec2(400.0, 100.0)),
+            vec2(400.0, 100.0),
+        );
+    }
 }
@@ -0,0 +1,176 @@
+use std::{
+    io::{ErrorKind, Write},
+    path::PathBuf,
+};
+
+// Folder inside the platform data directory, like ~/.local/share on Linux
+const GAME_DIR: &str = "rust-hotreload-example";
+const SLOT_EXTENSION: &str = "slot";
+
+pub fn default_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(GAME_DIR)
+}
+
+// Named save slots on disk, shared by both modes so a save from one loads in the other
+#[derive(Clone)]
+pub struct Storage {
+    dir: PathBuf,
+}
+
+impl Storage {
+    pub fn new(dir: impl Into<PathBuf>) -> Storage {
+        Storage { dir: dir.into() }
+    }
+
+    // Names map straight to file names, so anything that could be a path is refused
+    fn path(&self, slot: &str) -> Result<PathBuf, String> {
+        let valid = !slot.is_empty()
+            && slot
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(format!(
+                "Slot name {slot:?} may only use letters, digits, dashes and underscores"
+            ));
+        }
+        Ok(self.dir.join(format!("{slot}.{SLOT_EXTENSION}")))
+    }
+
+    pub fn get(&self, slot: &str) -> Result<Option<Vec<u8>>, String> {
+        match std::fs::read(self.path(slot)?) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Unable to read slot {slot}: {e}")),
+        }
+    }
+
+    // Written to a temporary file first and renamed over the slot, so a crash mid write keeps the
+    // previous contents
+    pub fn put(&self, slot: &str, data: &[u8]) -> Result<(), String> {
+        let path = self.path(slot)?;
+        let temporary = path.with_extension("tmp");
+        let write = || -> std::io::Result<()> {
+            std::fs::create_dir_all(&self.dir)?;
+            let mut file = std::fs::File::create(&temporary)?;
+            file.write_all(data)?;
+            file.sync_all()?;
+            std::fs::rename(&temporary, &path)
+        };
+        write().map_err(|e| format!("Unable to write slot {slot}: {e}"))
+    }
+
+    pub fn delete(&self, slot: &str) -> Result<(), String> {
+        match std::fs::remove_file(self.path(slot)?) {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                Err(format!("Unable to delete slot {slot}: {e}"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // A copy of every slot in another directory, for instances that must not change the real saves
+    #[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+    pub fn copy_to(&self, dir: impl Into<PathBuf>) -> Result<Storage, String> {
+        let copy = Storage::new(dir);
+        for slot in self.slots()? {
+            if let Some(data) = self.get(&slot)? {
+                copy.put(&slot, &data)?;
+            }
+        }
+        Ok(copy)
+    }
+
+    pub fn slots(&self) -> Result<Vec<String>, String> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(format!("Unable to list slots: {e}")),
+        };
+
+        let mut slots: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == SLOT_EXTENSION))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .collect();
+        slots.sort();
+        Ok(slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn valid_slot_names_map_to_slot_files() {
+        let storage = Storage::new("saves");
+        for slot in ["name", "save-1", "Slot_2"] {
+            assert_eq!(
+                storage.path(slot),
+                Ok(PathBuf::from(format!("saves/{slot}.slot")))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_slot_names_are_rejected() {
+        let storage = Storage::new("saves");
+        for slot in [
+            "",
+            ".",
+            "..",
+            "../name",
+            "a/b",
+            "a\\b",
+            "/etc/passwd",
+            "C:name",
+            "name.slot",
+            "two words",
+            "naïve",
+        ] {
+            assert!(storage.path(slot).is_err(), "{slot:?}");
+            assert!(storage.put(slot, b"data").is_err(), "{slot:?}");
+        }
+    }
+
+    #[test]
+    fn slots_round_trip_through_the_directory() {
+        let dir = TempDir::new("storage-round-trip");
+        let storage = Storage::new(dir.path());
+        assert_eq!(storage.slots(), Ok(vec![]));
+        assert_eq!(storage.get("name"), Ok(None));
+
+        storage.put("name", b"first").unwrap();
+        storage.put("name", b"second").unwrap();
+        storage.put("other", b"").unwrap();
+        assert_eq!(storage.get("name"), Ok(Some(b"second".to_vec())));
+        assert_eq!(
+            storage.slots(),
+            Ok(vec!["name".to_string(), "other".to_string()])
+        );
+        assert!(!dir.path().join("name.tmp").exists());
+
+        storage.delete("name").unwrap();
+        storage.delete("missing").unwrap();
+        assert_eq!(storage.get("name"), Ok(None));
+        assert_eq!(storage.slots(), Ok(vec!["other".to_string()]));
+    }
+
+    #[test]
+    fn copies_leave_the_original_slots_alone() {
+        let dir = TempDir::new("storage-copy");
+        let storage = Storage::new(dir.path().join("real"));
+        storage.put("name", b"saved").unwrap();
+
+        let copy = storage.copy_to(dir.path().join("copy")).unwrap();
+        assert_eq!(copy.get("name"), Ok(Some(b"saved".to_vec())));
+        copy.put("name", b"changed").unwrap();
+        copy.put("other", b"new").unwrap();
+        assert_eq!(storage.get("name"), Ok(Some(b"saved".to_vec())));
+        assert_eq!(storage.slots(), Ok(vec!["name".to_string()]));
+    }
+}
@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
 use anyhow::Result;
-use macroquad::texture::{load_texture, Texture2D};
+use macroquad::texture::{load_texture, render_target, RenderTarget, Texture2D};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct TextureCache {
     textures: HashMap<String, Texture2D>,
 }
@@ -19,3 +19,100 @@ impl TextureCache {
         }
     }
 }
+
+// Guest pixel data is padded or truncated to the texture size rather than trapping the guest
+fn fit_pixels(width: u32, height: u32, pixels: &[u8]) -> Cow<'_, [u8]> {
+    let expected = width as usize * height as usize * 4;
+    if pixels.len() == expected {
+        Cow::Borrowed(pixels)
+    } else {
+        let mut pixels = pixels.to_vec();
+        pixels.resize(expected, 0);
+        Cow::Owned(pixels)
+    }
+}
+
+// Largest side of a guest texture, which bounds the pixel buffer the host allocates to 64 MB
+pub const MAX_TEXTURE_SIZE: u32 = 4096;
+
+// Sizes come from the guest, so an empty texture becomes one pixel and a huge one is cut down
+pub fn create_texture(width: u32, height: u32, pixels: &[u8]) -> Texture2D {
+    let width = width.clamp(1, MAX_TEXTURE_SIZE);
+    let height = height.clamp(1, MAX_TEXTURE_SIZE);
+    Texture2D::from_rgba8(
+        width as u16,
+        height as u16,
+        &fit_pixels(width, height, pixels),
+    )
+}
+
+// Keeps the top left corner of a frame too large for a texture, row by row, so the remaining rows
+// still line up with the narrower texture
+pub fn crop_pixels(width: u32, height: u32, pixels: &[u8]) -> (u32, u32, Cow<'_, [u8]>) {
+    let (cropped_width, cropped_height) =
+        (width.min(MAX_TEXTURE_SIZE), height.min(MAX_TEXTURE_SIZE));
+    if cropped_width == width {
+        return (width, cropped_height, Cow::Borrowed(pixels));
+    }
+    let row = cropped_width as usize * 4;
+    let pixels = pixels
+        .chunks(width as usize * 4)
+        .take(cropped_height as usize)
+        .flat_map(|pixels| &pixels[..row.min(pixels.len())])
+        .copied()
+        .collect();
+    (cropped_width, cropped_height, Cow::Owned(pixels))
+}
+
+// Render targets get the same limits, a negative or NaN side becomes one pixel as well
+pub fn create_render_target(width: f32, height: f32) -> RenderTarget {
+    render_target(
+        (width as u32).clamp(1, MAX_TEXTURE_SIZE),
+        (height as u32).clamp(1, MAX_TEXTURE_SIZE),
+    )
+}
+
+pub fn update_texture(texture: &Texture2D, pixels: &[u8]) {
+    let (width, height) = (texture.width() as u32, texture.height() as u32);
+    texture.update_from_bytes(width, height, &fit_pixels(width, height, pixels));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_within_the_limit_are_not_copied() {
+        let pixels = vec![7; 4 * 3 * 4];
+        let (width, height, cropped) = crop_pixels(4, 3, &pixels);
+        assert_eq!((width, height), (4, 3));
+        assert!(matches!(cropped, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn wide_frames_keep_the_left_of_each_row() {
+        let width = MAX_TEXTURE_SIZE + 2;
+        // Each pixel holds the column it came from, so shifted rows would show up as a mismatch
+        let pixels: Vec<u8> = (0..2)
+            .flat_map(|_| (0..width).flat_map(|column| [column as u8; 4]))
+            .collect();
+        let (cropped_width, height, cropped) = crop_pixels(width, 2, &pixels);
+        assert_eq!((cropped_width, height), (MAX_TEXTURE_SIZE, 2));
+        assert_eq!(cropped.len(), MAX_TEXTURE_SIZE as usize * 2 * 4);
+        for row in cropped.chunks(MAX_TEXTURE_SIZE as usize * 4) {
+            for (column, pixel) in row.chunks(4).enumerate() {
+                assert_eq!(pixel, [column as u8; 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn tall_frames_keep_the_top_rows() {
+        let height = MAX_TEXTURE_SIZE + 5;
+        let pixels = vec![1; height as usize * 4];
+        let (width, cropped_height, cropped) = crop_pixels(1, height, &pixels);
+        assert_eq!((width, cropped_height), (1, MAX_TEXTURE_SIZE));
+        // Texture creation truncates the extra rows, so they aren't copied here
+        assert_eq!(cropped.len(), height as usize * 4);
+    }
+}
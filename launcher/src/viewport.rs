@@ -0,0 +1,172 @@
+use macroquad::{
+    math::{vec2, Rect, Vec2},
+    window::{screen_height, screen_width},
+};
+
+#[cfg(not(feature = "hotreload"))]
+use game::example::game::types::{ScalingMode, VirtualResolution};
+
+#[cfg(feature = "hotreload")]
+use crate::hotreload::binding::example::game::types::{ScalingMode, VirtualResolution};
+
+// Maps guest coordinates onto the surface being drawn to, letterboxing whatever is left over
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    // Size of the window or render target, in pixels
+    pub surface: Vec2,
+    // Size of the guest coordinate space
+    pub size: Vec2,
+    pub scale: f32,
+    pub offset: Vec2,
+    letterboxed: bool,
+}
+
+impl Viewport {
+    pub fn new(resolution: Option<VirtualResolution>) -> Viewport {
+        Viewport::fit(vec2(screen_width(), screen_height()), resolution)
+    }
+
+    pub fn fit(window: Vec2, resolution: Option<VirtualResolution>) -> Viewport {
+        let Some(resolution) = resolution else {
+            return Viewport::identity(window);
+        };
+
+        let size = vec2(resolution.size.width, resolution.size.height);
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return Viewport::identity(window);
+        }
+
+        let fit = (window.x / size.x).min(window.y / size.y);
+        let scale = match resolution.scaling {
+            ScalingMode::Fit => fit,
+            ScalingMode::Fill => (window.x / size.x).max(window.y / size.y),
+            ScalingMode::Integer => fit.floor().max(1.0),
+        };
+
+        Viewport {
+            surface: window,
+            size,
+            scale,
+            offset: (window - size * scale) / 2.0,
+            letterboxed: true,
+        }
+    }
+
+    pub fn identity(surface: Vec2) -> Viewport {
+        Viewport {
+            surface,
+            size: surface,
+            scale: 1.0,
+            offset: Vec2::ZERO,
+            letterboxed: false,
+        }
+    }
+
+    pub fn to_virtual(self, position: Vec2) -> Vec2 {
+        (position - self.offset) / self.scale
+    }
+
+    pub fn to_surface(self, position: Vec2) -> Vec2 {
+        position * self.scale + self.offset
+    }
+
+    pub fn to_surface_rect(self, rect: Rect) -> Rect {
+        let position = self.to_surface(rect.point());
+        Rect::new(
+            position.x,
+            position.y,
+            rect.w * self.scale,
+            rect.h * self.scale,
+        )
+    }
+
+    // The visible part of the virtual resolution, which drawing is clipped to
+    pub fn bounds(self) -> Option<Rect> {
+        self.letterboxed.then(|| {
+            let surface = Rect::new(0.0, 0.0, self.surface.x, self.surface.y);
+            let area = self.to_surface_rect(Rect::new(0.0, 0.0, self.size.x, self.size.y));
+            surface
+                .intersect(area)
+                .unwrap_or(Rect::new(0.0, 0.0, 0.0, 0.0))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "hotreload"))]
+    use game::example::game::types::Size;
+
+    #[cfg(feature = "hotreload")]
+    use crate::hotreload::binding::example::game::types::Size;
+
+    fn viewport(window: Vec2, width: f32, height: f32, scaling: ScalingMode) -> Viewport {
+        Viewport::fit(
+            window,
+            Some(VirtualResolution {
+                size: Size { width, height },
+                scaling,
+            }),
+        )
+    }
+
+    fn assert_round_trips(viewport: Viewport) {
+        for position in [vec2(0.0, 0.0), vec2(13.5, 7.25), viewport.size] {
+            let back = viewport.to_virtual(viewport.to_surface(position));
+            assert!(
+                (back - position).length() < 1e-3,
+                "{position} came back as {back}"
+            );
+        }
+    }
+
+    #[test]
+    fn fit_letterboxes_the_longer_side() {
+        let viewport = viewport(vec2(1000.0, 600.0), 320.0, 240.0, ScalingMode::Fit);
+        assert_eq!(viewport.scale, 2.5);
+        assert_eq!(viewport.offset, vec2(100.0, 0.0));
+        assert_eq!(viewport.to_surface(vec2(0.0, 0.0)), vec2(100.0, 0.0));
+        assert_eq!(viewport.to_surface(vec2(320.0, 240.0)), vec2(900.0, 600.0));
+        assert_eq!(viewport.bounds(), Some(Rect::new(100.0, 0.0, 800.0, 600.0)));
+        assert_round_trips(viewport);
+    }
+
+    #[test]
+    fn fill_crops_the_shorter_side() {
+        let viewport = viewport(vec2(1000.0, 600.0), 320.0, 240.0, ScalingMode::Fill);
+        assert_eq!(viewport.scale, 3.125);
+        assert_eq!(viewport.offset, vec2(0.0, -75.0));
+        assert_eq!(viewport.bounds(), Some(Rect::new(0.0, 0.0, 1000.0, 600.0)));
+        assert_round_trips(viewport);
+    }
+
+    #[test]
+    fn integer_rounds_the_scale_down() {
+        let viewport = viewport(vec2(1000.0, 600.0), 320.0, 240.0, ScalingMode::Integer);
+        assert_eq!(viewport.scale, 2.0);
+        assert_eq!(viewport.offset, vec2(180.0, 60.0));
+        assert_round_trips(viewport);
+    }
+
+    #[test]
+    fn integer_never_scales_below_one() {
+        let viewport = viewport(vec2(200.0, 100.0), 320.0, 240.0, ScalingMode::Integer);
+        assert_eq!(viewport.scale, 1.0);
+        assert_eq!(viewport.offset, vec2(-60.0, -70.0));
+        assert_round_trips(viewport);
+    }
+
+    #[test]
+    fn empty_resolution_uses_window_pixels() {
+        let window = vec2(800.0, 600.0);
+        for (width, height) in [(0.0, 240.0), (320.0, -1.0)] {
+            let viewport = viewport(window, width, height, ScalingMode::Fit);
+            assert_eq!(viewport.scale, 1.0);
+            assert_eq!(viewport.size, window);
+            assert_eq!(viewport.bounds(), None);
+        }
+        assert_eq!(Viewport::fit(window, None).bounds(), None);
+    }
+}
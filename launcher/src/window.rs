@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex};
+
+use macroquad::{
+    color::Color,
+    input::{is_quit_requested, prevent_quit},
+    window::{clear_background, request_new_screen_size, set_fullscreen},
+};
+
+use crate::{
+    log::{Log, LogLevel},
+    screen::convert_color,
+};
+
+#[cfg(not(feature = "hotreload"))]
+use game::example::game::types::{GameColor, Size};
+
+#[cfg(feature = "hotreload")]
+use crate::hotreload::binding::example::game::types::{GameColor, Size};
+
+#[derive(Default)]
+struct WindowState {
+    clear_color: Option<Color>,
+    quit: bool,
+}
+
+// Window operations requested by the guest, the window itself outlives every reload
+#[derive(Clone)]
+pub struct Window {
+    state: Arc<Mutex<WindowState>>,
+    log: Log,
+    // Belongs to an instance that isn't shown, so its requests are ignored
+    headless: bool,
+}
+
+impl Window {
+    // Closing the window only requests a quit, so the game can be told before the launcher exits
+    pub fn new(log: Log) -> Window {
+        prevent_quit();
+        Window {
+            state: Arc::new(Mutex::new(WindowState::default())),
+            log,
+            headless: false,
+        }
+    }
+
+    #[cfg_attr(not(feature = "hotreload"), allow(dead_code))]
+    pub fn headless(log: Log) -> Window {
+        Window {
+            state: Arc::new(Mutex::new(WindowState::default())),
+            log,
+            headless: true,
+        }
+    }
+
+    // Macroquad has no way to rename the window once it is open
+    pub fn set_title(&self, title: &str) {
+        if self.headless {
+            return;
+        }
+        self.log.write(
+            LogLevel::Warn,
+            "window",
+            &format!("Window title can't be changed after startup, ignoring \"{title}\""),
+        );
+    }
+
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        if self.headless {
+            return;
+        }
+        set_fullscreen(fullscreen);
+    }
+
+    pub fn set_clear_color(&self, color: GameColor) {
+        if self.headless {
+            return;
+        }
+        self.state.lock().unwrap().clear_color = Some(convert_color(color));
+    }
+
+    pub fn resize(&self, size: Size) {
+        if self.headless {
+            return;
+        }
+        request_new_screen_size(size.width, size.height);
+    }
+
+    pub fn quit(&self) {
+        if self.headless {
+            return;
+        }
+        self.state.lock().unwrap().quit = true;
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.state.lock().unwrap().quit || is_quit_requested()
+    }
+
+    // Draws are only flushed at the end of the frame, so this can be called any time before that
+    pub fn clear(&self) {
+        if let Some(color) = self.state.lock().unwrap().clear_color {
+            clear_background(color);
+        }
+    }
+}